@@ -1,36 +1,50 @@
 use imgui::Condition;
 use std::borrow::Cow;
 
-use imgui_rs_overlay::key_down;
 use imgui_rs_overlay::window::{FrameRate, Windows, WindowsOptions};
+use imgui_rs_overlay::{FontRole, Hotkeys, Key, Theme};
 
 fn main() -> imgui_rs_overlay::Result<()> {
     let mut index = 2usize;
-    let items = ["Dark", "Highlight", "Classic"];
+    let themes = Theme::all_bundled();
+    let mut font_size = 14.0f32;
+    let mut hotkeys = Hotkeys::new();
+    hotkeys.bind("exit", Key::End);
+    hotkeys.bind("toggle", Key::Home);
     let mut app = Windows::new(&WindowsOptions {
         frame_rate: FrameRate::SYNC_SCREEN,
+        hotkeys,
         ..WindowsOptions::default()
     })?;
-    app.run(move |ui, style| {
+    app.run(move |ui, frame| {
         ui.window("你好")
             .resizable(true)
             .size([250.0, 100.0], Condition::FirstUseEver)
             .movable(true)
             .build(|| {
-                if ui.combo("theme", &mut index, &items, |item| {
-                    Cow::Owned(String::from(*item))
+                if ui.combo("theme", &mut index, &themes, |theme| {
+                    Cow::Borrowed(theme.name.as_str())
                 }) {
-                    match index {
-                        0 => style.use_dark_colors(),
-                        1 => style.use_light_colors(),
-                        2 => style.use_classic_colors(),
-                        _ => style,
-                    };
+                    frame.set_theme(themes[index].clone());
                 }
+                ui.slider("font size", 10.0, 24.0, &mut font_size);
+                // 松开滑块后再重建字体图集
+                if ui.is_item_deactivated_after_edit() {
+                    frame.set_font_size(FontRole::Body, font_size);
+                }
+                // 标题字体的中文按需加载，绘制前登记
+                frame.request_glyphs("覆盖层");
+                let title = ui.push_font(frame.font(FontRole::Title));
+                ui.text("覆盖层");
+                title.pop();
                 ui.text(format!("FPS: {:.2}", ui.io().framerate));
                 ui.text("hello world!");
             });
-        !key_down!(35)
+        // 淡入淡出地显示或隐藏
+        if frame.hotkeys().pressed("toggle") {
+            frame.set_visible(!frame.is_visible());
+        }
+        !frame.hotkeys().pressed("exit")
     })?;
     Ok(())
 }
@@ -9,6 +9,8 @@ fn main() {
         .define("CIMGUI_NO_EXPORT", None)
         .define("IMGUI_DISABLE_WIN32_FUNCTIONS", None)
         .define("IMGUI_DISABLE_OSX_FUNCTIONS", None)
+        // 手柄由Rust读取并经过输入管线转发
+        .define("IMGUI_IMPL_WIN32_DISABLE_GAMEPAD", None)
         .compile("lingex_imgui_impl");
     println!("cargo:rustc-link-lib=static=lingex_imgui_impl")
 }
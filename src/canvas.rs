@@ -0,0 +1,165 @@
+/// 虚拟画布适配到覆盖窗口的方式
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum FitMode {
+    /// 拉伸填满，横纵比例可能不同
+    Stretch,
+    /// 等比缩放完整显示，多余部分留黑边
+    #[default]
+    Letterbox,
+    /// 等比缩放填满，超出部分被裁剪
+    Crop,
+}
+
+/// 虚拟分辨率，HUD元素按该分辨率布局后再映射到覆盖窗口
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct VirtualCanvas {
+    pub width: f32,
+    pub height: f32,
+    pub fit: FitMode,
+}
+
+impl VirtualCanvas {
+    pub const fn new(width: f32, height: f32, fit: FitMode) -> VirtualCanvas {
+        VirtualCanvas { width, height, fit }
+    }
+}
+
+/// 虚拟坐标到覆盖窗口像素坐标的映射，覆盖窗口大小变化时重新计算
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CanvasMapping {
+    scale: [f32; 2],
+    offset: [f32; 2],
+    size: [f32; 2],
+}
+
+impl Default for CanvasMapping {
+    fn default() -> Self {
+        CanvasMapping::identity([0.0, 0.0])
+    }
+}
+
+impl CanvasMapping {
+    /// 不做任何变换，虚拟坐标即像素坐标
+    pub fn identity(overlay_size: [f32; 2]) -> CanvasMapping {
+        CanvasMapping {
+            scale: [1.0, 1.0],
+            offset: [0.0, 0.0],
+            size: overlay_size,
+        }
+    }
+
+    /// 根据覆盖窗口大小计算映射
+    pub fn new(canvas: &VirtualCanvas, overlay_size: [f32; 2]) -> CanvasMapping {
+        if canvas.width <= 0.0 || canvas.height <= 0.0 {
+            return CanvasMapping::identity(overlay_size);
+        }
+        let sx = overlay_size[0] / canvas.width;
+        let sy = overlay_size[1] / canvas.height;
+        let scale = match canvas.fit {
+            FitMode::Stretch => [sx, sy],
+            FitMode::Letterbox => [sx.min(sy); 2],
+            FitMode::Crop => [sx.max(sy); 2],
+        };
+        let offset = [
+            (overlay_size[0] - canvas.width * scale[0]) / 2.0,
+            (overlay_size[1] - canvas.height * scale[1]) / 2.0,
+        ];
+        CanvasMapping {
+            scale,
+            offset,
+            size: overlay_size,
+        }
+    }
+
+    /// 虚拟坐标转换为覆盖窗口像素坐标
+    pub fn map_pos(&self, pos: [f32; 2]) -> [f32; 2] {
+        [
+            self.offset[0] + pos[0] * self.scale[0],
+            self.offset[1] + pos[1] * self.scale[1],
+        ]
+    }
+
+    /// 虚拟尺寸转换为像素尺寸
+    pub fn map_size(&self, size: [f32; 2]) -> [f32; 2] {
+        [size[0] * self.scale[0], size[1] * self.scale[1]]
+    }
+
+    /// 像素坐标转换回虚拟坐标
+    pub fn unmap_pos(&self, pos: [f32; 2]) -> [f32; 2] {
+        [
+            (pos[0] - self.offset[0]) / self.scale[0],
+            (pos[1] - self.offset[1]) / self.scale[1],
+        ]
+    }
+
+    /// 横纵缩放比例
+    pub fn scale(&self) -> [f32; 2] {
+        self.scale
+    }
+
+    /// 虚拟画布在覆盖窗口中的左上角与右下角(裁剪模式下可能超出窗口)
+    pub fn viewport(&self) -> ([f32; 2], [f32; 2]) {
+        let min = self.offset;
+        let max = [self.size[0] - self.offset[0], self.size[1] - self.offset[1]];
+        (min, max)
+    }
+
+    /// 覆盖窗口大小
+    pub fn overlay_size(&self) -> [f32; 2] {
+        self.size
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CANVAS: [f32; 2] = [1920.0, 1080.0];
+
+    fn mapping(fit: FitMode, overlay_size: [f32; 2]) -> CanvasMapping {
+        CanvasMapping::new(&VirtualCanvas::new(CANVAS[0], CANVAS[1], fit), overlay_size)
+    }
+
+    #[test]
+    fn stretch_fills_the_overlay() {
+        let mapping = mapping(FitMode::Stretch, [960.0, 1080.0]);
+        assert_eq!(mapping.scale(), [0.5, 1.0]);
+        assert_eq!(mapping.map_pos([0.0, 0.0]), [0.0, 0.0]);
+        assert_eq!(mapping.map_pos(CANVAS), [960.0, 1080.0]);
+    }
+
+    #[test]
+    fn letterbox_centers_the_canvas() {
+        // 4:3的窗口，上下留黑边
+        let mapping = mapping(FitMode::Letterbox, [960.0, 720.0]);
+        assert_eq!(mapping.scale(), [0.5, 0.5]);
+        assert_eq!(mapping.viewport(), ([0.0, 90.0], [960.0, 630.0]));
+        assert_eq!(mapping.map_pos(CANVAS), [960.0, 630.0]);
+    }
+
+    #[test]
+    fn crop_overflows_the_overlay() {
+        let mapping = mapping(FitMode::Crop, [960.0, 720.0]);
+        let scale = 720.0 / 1080.0;
+        assert_eq!(mapping.scale(), [scale, scale]);
+        let (min, max) = mapping.viewport();
+        assert!(min[0] < 0.0 && max[0] > 960.0);
+        assert_eq!([min[1], max[1]], [0.0, 720.0]);
+    }
+
+    #[test]
+    fn unmap_reverses_map() {
+        for fit in [FitMode::Stretch, FitMode::Letterbox, FitMode::Crop] {
+            let mapping = mapping(fit, [1280.0, 1024.0]);
+            let pos = mapping.unmap_pos(mapping.map_pos([400.0, 300.0]));
+            assert!((pos[0] - 400.0).abs() < 1e-3 && (pos[1] - 300.0).abs() < 1e-3);
+        }
+    }
+
+    #[test]
+    fn empty_canvas_is_identity() {
+        let canvas = VirtualCanvas::new(0.0, 0.0, FitMode::Letterbox);
+        let mapping = CanvasMapping::new(&canvas, [800.0, 600.0]);
+        assert_eq!(mapping, CanvasMapping::identity([800.0, 600.0]));
+    }
+}
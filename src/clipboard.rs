@@ -0,0 +1,199 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+#[cfg(all(unix, feature = "x11"))]
+use std::time::Duration;
+
+use imgui::ClipboardBackend;
+use windows::Win32::Foundation::{GlobalFree, HANDLE, HGLOBAL, HWND};
+use windows::Win32::System::DataExchange::{
+    CloseClipboard, EmptyClipboard, GetClipboardData, IsClipboardFormatAvailable, OpenClipboard,
+    SetClipboardData,
+};
+use windows::Win32::System::Memory::{GlobalAlloc, GlobalLock, GlobalUnlock, GMEM_MOVEABLE};
+use windows::Win32::System::Ole::CF_UNICODETEXT;
+
+/// Windows系统剪贴板，覆盖窗口默认使用
+pub struct Win32Clipboard {
+    owner: HWND,
+}
+
+impl Win32Clipboard {
+    /// [owner] 剪贴板所有者窗口
+    pub fn new(owner: HWND) -> Win32Clipboard {
+        Win32Clipboard { owner }
+    }
+}
+
+/// 打开剪贴板，离开作用域时关闭
+struct OpenedClipboard;
+
+impl OpenedClipboard {
+    fn open(owner: HWND) -> Option<OpenedClipboard> {
+        match unsafe { OpenClipboard(Some(owner)) } {
+            Ok(()) => Some(OpenedClipboard),
+            Err(e) => {
+                log::warn!("OpenClipboard failed: {e}");
+                None
+            }
+        }
+    }
+}
+
+impl Drop for OpenedClipboard {
+    fn drop(&mut self) {
+        let _ = unsafe { CloseClipboard() };
+    }
+}
+
+impl ClipboardBackend for Win32Clipboard {
+    fn get(&mut self) -> Option<String> {
+        unsafe {
+            IsClipboardFormatAvailable(CF_UNICODETEXT.0 as u32).ok()?;
+            let _clipboard = OpenedClipboard::open(self.owner)?;
+            let handle = GetClipboardData(CF_UNICODETEXT.0 as u32).ok()?;
+            let memory = HGLOBAL(handle.0);
+            let data = GlobalLock(memory) as *const u16;
+            if data.is_null() {
+                return None;
+            }
+            let mut len = 0;
+            while *data.add(len) != 0 {
+                len += 1;
+            }
+            let text = String::from_utf16_lossy(std::slice::from_raw_parts(data, len));
+            let _ = GlobalUnlock(memory);
+            Some(text)
+        }
+    }
+
+    fn set(&mut self, value: &str) {
+        let text: Vec<u16> = value.encode_utf16().chain(Some(0)).collect();
+        unsafe {
+            let Some(_clipboard) = OpenedClipboard::open(self.owner) else {
+                return;
+            };
+            let _ = EmptyClipboard();
+            let memory = match GlobalAlloc(GMEM_MOVEABLE, text.len() * size_of::<u16>()) {
+                Ok(memory) => memory,
+                Err(e) => {
+                    log::warn!("GlobalAlloc failed: {e}");
+                    return;
+                }
+            };
+            let data = GlobalLock(memory) as *mut u16;
+            if data.is_null() {
+                let _ = GlobalFree(Some(memory));
+                return;
+            }
+            std::ptr::copy_nonoverlapping(text.as_ptr(), data, text.len());
+            let _ = GlobalUnlock(memory);
+            // 设置成功后内存归系统所有
+            if let Err(e) = SetClipboardData(CF_UNICODETEXT.0 as u32, Some(HANDLE(memory.0))) {
+                log::warn!("SetClipboardData failed: {e}");
+                let _ = GlobalFree(Some(memory));
+            }
+        }
+    }
+}
+
+/// X11的选区
+#[cfg(all(unix, feature = "x11"))]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum X11Selection {
+    /// 复制粘贴使用的`CLIPBOARD`
+    #[default]
+    Clipboard,
+    /// 选中即复制、中键粘贴的`PRIMARY`
+    Primary,
+}
+
+/// 读取选区时等待选区所有者响应的时间
+#[cfg(all(unix, feature = "x11"))]
+const X11_LOAD_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// X11选区，需开启`x11`特性。写入后由后台线程响应其它程序的读取请求
+#[cfg(all(unix, feature = "x11"))]
+pub struct X11Clipboard {
+    clipboard: x11_clipboard::Clipboard,
+    selection: X11Selection,
+}
+
+#[cfg(all(unix, feature = "x11"))]
+impl X11Clipboard {
+    /// 连接`DISPLAY`指定的X服务器
+    pub fn new(selection: X11Selection) -> Result<X11Clipboard, x11_clipboard::error::Error> {
+        Ok(X11Clipboard {
+            clipboard: x11_clipboard::Clipboard::new()?,
+            selection,
+        })
+    }
+
+    pub fn selection(&self) -> X11Selection {
+        self.selection
+    }
+
+    fn selection_atom(&self, atoms: &x11_clipboard::Atoms) -> x11_clipboard::Atom {
+        match self.selection {
+            X11Selection::Clipboard => atoms.clipboard,
+            X11Selection::Primary => atoms.primary,
+        }
+    }
+}
+
+#[cfg(all(unix, feature = "x11"))]
+impl ClipboardBackend for X11Clipboard {
+    fn get(&mut self) -> Option<String> {
+        let atoms = &self.clipboard.getter.atoms;
+        let selection = self.selection_atom(atoms);
+        match self.clipboard.load(
+            selection,
+            atoms.utf8_string,
+            atoms.property,
+            X11_LOAD_TIMEOUT,
+        ) {
+            Ok(data) => Some(String::from_utf8_lossy(&data).into_owned()),
+            Err(e) => {
+                log::warn!("Failed to read X11 selection {:?}: {e}", self.selection);
+                None
+            }
+        }
+    }
+
+    fn set(&mut self, value: &str) {
+        let atoms = &self.clipboard.setter.atoms;
+        let selection = self.selection_atom(atoms);
+        if let Err(e) = self.clipboard.store(selection, atoms.utf8_string, value) {
+            log::warn!("Failed to set X11 selection {:?}: {e}", self.selection);
+        }
+    }
+}
+
+/// 进程内剪贴板，用于无窗口运行与测试，克隆后共享同一内容
+#[derive(Clone, Debug, Default)]
+pub struct MemoryClipboard {
+    contents: Rc<RefCell<Option<String>>>,
+}
+
+impl MemoryClipboard {
+    pub fn new() -> MemoryClipboard {
+        MemoryClipboard::default()
+    }
+
+    pub fn contents(&self) -> Option<String> {
+        self.contents.borrow().clone()
+    }
+
+    pub fn set_contents(&self, value: Option<String>) {
+        *self.contents.borrow_mut() = value;
+    }
+}
+
+impl ClipboardBackend for MemoryClipboard {
+    fn get(&mut self) -> Option<String> {
+        self.contents()
+    }
+
+    fn set(&mut self, value: &str) {
+        self.set_contents(Some(value.to_owned()));
+    }
+}
@@ -0,0 +1,58 @@
+use std::cell::Cell;
+
+use imgui::MouseCursor;
+use windows::core::PCWSTR;
+use windows::Win32::Foundation::LPARAM;
+use windows::Win32::UI::WindowsAndMessaging::{
+    LoadCursorW, SetCursor, HTCLIENT, IDC_ARROW, IDC_HAND, IDC_IBEAM, IDC_NO, IDC_SIZEALL,
+    IDC_SIZENESW, IDC_SIZENS, IDC_SIZENWSE, IDC_SIZEWE, WM_SETCURSOR,
+};
+
+thread_local! {
+    /// imgui最近一次要求的光标，None表示隐藏
+    static CURSOR: Cell<Option<MouseCursor>> = const { Cell::new(Some(MouseCursor::Arrow)) };
+}
+
+/// imgui光标对应的系统光标
+fn win32_cursor(cursor: MouseCursor) -> PCWSTR {
+    match cursor {
+        MouseCursor::Arrow => IDC_ARROW,
+        MouseCursor::TextInput => IDC_IBEAM,
+        MouseCursor::ResizeAll => IDC_SIZEALL,
+        MouseCursor::ResizeNS => IDC_SIZENS,
+        MouseCursor::ResizeEW => IDC_SIZEWE,
+        MouseCursor::ResizeNESW => IDC_SIZENESW,
+        MouseCursor::ResizeNWSE => IDC_SIZENWSE,
+        MouseCursor::Hand => IDC_HAND,
+        MouseCursor::NotAllowed => IDC_NO,
+    }
+}
+
+fn apply(cursor: Option<MouseCursor>) {
+    unsafe {
+        match cursor {
+            Some(cursor) => {
+                let _ = SetCursor(LoadCursorW(None, win32_cursor(cursor)).ok());
+            }
+            None => {
+                let _ = SetCursor(None);
+            }
+        }
+    }
+}
+
+/// 设置覆盖窗口的光标，变化时立即生效
+pub(crate) fn set_cursor(cursor: Option<MouseCursor>) {
+    if CURSOR.with(|current| current.replace(cursor)) != cursor {
+        apply(cursor);
+    }
+}
+
+/// 窗口样式切换或光标移入时系统会重置光标，返回true表示已处理
+pub(crate) fn handle_win32_message(msg: u32, lparam: LPARAM) -> bool {
+    if msg != WM_SETCURSOR || (lparam.0 & 0xFFFF) as u32 != HTCLIENT {
+        return false;
+    }
+    apply(CURSOR.with(|current| current.get()));
+    true
+}
@@ -0,0 +1,593 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::ffi::CStr;
+use std::fs;
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+
+use imgui::internal::RawCast;
+use imgui::{sys, FontAtlas};
+
+const MAGIC: &[u8; 8] = b"IMATLAS\0";
+const FORMAT_VERSION: u32 = 1;
+/// 缓存目录中保留的图集数量，不同DPI与字号各占一个
+const MAX_ENTRIES: usize = 8;
+/// 识别同一字体数据时取样的首尾字节数
+const FINGERPRINT_LEN: usize = 4096;
+
+/// 字体图集磁盘缓存，保存光栅化后的像素与字形数据，启动时跳过光栅化。
+/// 以字体文件内容、字号、字形范围与字体配置的哈希为键，任一变化时自动失效。
+/// 运行中按需加载字符后重建的图集不读写缓存，避免挤掉启动时的图集
+#[derive(Clone, Debug)]
+pub struct FontAtlasCache {
+    dir: PathBuf,
+    /// 字体数据的哈希，以长度与首尾字节为键，同一字体文件只完整哈希一次
+    data_hashes: RefCell<HashMap<(usize, u64), u64>>,
+}
+
+impl FontAtlasCache {
+    pub fn new(dir: impl Into<PathBuf>) -> FontAtlasCache {
+        FontAtlasCache {
+            dir: dir.into(),
+            data_hashes: RefCell::new(HashMap::new()),
+        }
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// 加入字体后调用，缓存命中时直接载入，否则光栅化并写入缓存。返回是否命中
+    pub fn build(&self, atlas: &mut FontAtlas) -> bool {
+        let key = unsafe { self.atlas_key(atlas.raw()) };
+        let path = self.dir.join(format!("{key:016x}.atlas"));
+        match self.load(&path, key, atlas) {
+            Ok(true) => {
+                log::debug!("Loaded font atlas from {}", path.display());
+                return true;
+            }
+            Ok(false) => {}
+            Err(e) => log::warn!("Failed to load font atlas {}: {e}", path.display()),
+        }
+        if !unsafe { sys::ImFontAtlas_Build(atlas.raw_mut()) } {
+            log::warn!("Failed to build font atlas");
+            return false;
+        }
+        if let Err(e) = self.save(&path, key, atlas) {
+            log::warn!("Failed to save font atlas {}: {e}", path.display());
+        }
+        false
+    }
+
+    fn load(&self, path: &Path, key: u64, atlas: &mut FontAtlas) -> io::Result<bool> {
+        let data = match fs::read(path) {
+            Ok(data) => data,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(false),
+            Err(e) => return Err(e),
+        };
+        let mut reader = data.as_slice();
+        let mut magic = [0u8; 8];
+        reader.read_exact(&mut magic)?;
+        if &magic != MAGIC || read_u32(&mut reader)? != FORMAT_VERSION {
+            return Ok(false);
+        }
+        if read_u64(&mut reader)? != key {
+            return Ok(false);
+        }
+        let image = AtlasImage::read(&mut reader)?;
+        unsafe { image.apply(atlas.raw_mut()) }
+        Ok(true)
+    }
+
+    fn save(&self, path: &Path, key: u64, atlas: &FontAtlas) -> io::Result<()> {
+        fs::create_dir_all(&self.dir)?;
+        let mut data = Vec::new();
+        data.extend_from_slice(MAGIC);
+        data.extend_from_slice(&FORMAT_VERSION.to_le_bytes());
+        data.extend_from_slice(&key.to_le_bytes());
+        unsafe { AtlasImage::capture(atlas.raw()) }.write(&mut data)?;
+        // 先写临时文件再改名，避免中断时留下不完整的缓存
+        let temp = path.with_extension("tmp");
+        fs::write(&temp, data)?;
+        fs::rename(&temp, path)?;
+        self.prune();
+        Ok(())
+    }
+
+    /// 只保留最近使用的若干个图集
+    fn prune(&self) {
+        let Ok(entries) = fs::read_dir(&self.dir) else {
+            return;
+        };
+        let mut files: Vec<(std::time::SystemTime, PathBuf)> = entries
+            .flatten()
+            .filter(|entry| entry.path().extension().is_some_and(|ext| ext == "atlas"))
+            .filter_map(|entry| Some((entry.metadata().ok()?.modified().ok()?, entry.path())))
+            .collect();
+        files.sort_by_key(|(modified, _)| std::cmp::Reverse(*modified));
+        for (_, path) in files.into_iter().skip(MAX_ENTRIES) {
+            let _ = fs::remove_file(path);
+        }
+    }
+}
+
+/// FNV-1a，结果不随编译器版本变化
+struct Fnv(u64);
+
+impl Fnv {
+    fn new() -> Fnv {
+        Fnv(0xcbf2_9ce4_8422_2325)
+    }
+
+    fn bytes(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= *byte as u64;
+            self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+
+    fn u32(&mut self, value: u32) {
+        self.bytes(&value.to_le_bytes());
+    }
+
+    fn u64(&mut self, value: u64) {
+        self.bytes(&value.to_le_bytes());
+    }
+
+    fn f32(&mut self, value: f32) {
+        self.u32(value.to_bits());
+    }
+}
+
+unsafe fn slice<'a, T>(data: *const T, len: i32) -> &'a [T] {
+    if data.is_null() || len <= 0 {
+        &[]
+    } else {
+        std::slice::from_raw_parts(data, len as usize)
+    }
+}
+
+impl FontAtlasCache {
+    /// 每个角色都复制了一份字体数据，完整的哈希按首尾字节缓存
+    fn data_hash(&self, data: &[u8]) -> u64 {
+        let mut fingerprint = Fnv::new();
+        fingerprint.bytes(&data[..data.len().min(FINGERPRINT_LEN)]);
+        fingerprint.bytes(&data[data.len().saturating_sub(FINGERPRINT_LEN)..]);
+        *self
+            .data_hashes
+            .borrow_mut()
+            .entry((data.len(), fingerprint.0))
+            .or_insert_with(|| {
+                let mut hash = Fnv::new();
+                hash.bytes(data);
+                hash.0
+            })
+    }
+
+    /// 影响光栅化结果的所有输入
+    unsafe fn atlas_key(&self, atlas: &sys::ImFontAtlas) -> u64 {
+        let mut hash = Fnv::new();
+        hash.bytes(CStr::from_ptr(sys::igGetVersion()).to_bytes());
+        // 两种光栅化器的结果不同
+        hash.u32(cfg!(feature = "freetype") as u32);
+        hash.u32(atlas.Flags as u32);
+        hash.u32(atlas.TexDesiredWidth as u32);
+        hash.u32(atlas.TexGlyphPadding as u32);
+        hash.u32(atlas.FontBuilderFlags);
+        hash.u32(atlas.Fonts.Size as u32);
+        let fonts = slice(atlas.Fonts.Data, atlas.Fonts.Size);
+        for config in slice(atlas.ConfigData.Data, atlas.ConfigData.Size) {
+            hash.u64(self.data_hash(slice(config.FontData as *const u8, config.FontDataSize)));
+            hash.u32(config.FontNo as u32);
+            hash.f32(config.SizePixels);
+            hash.u32(config.OversampleH as u32);
+            hash.u32(config.OversampleV as u32);
+            hash.u32(config.PixelSnapH as u32);
+            for value in [
+                config.GlyphExtraSpacing.x,
+                config.GlyphExtraSpacing.y,
+                config.GlyphOffset.x,
+                config.GlyphOffset.y,
+                config.GlyphMinAdvanceX,
+                config.GlyphMaxAdvanceX,
+                config.RasterizerMultiply,
+            ] {
+                hash.f32(value);
+            }
+            hash.u32(config.MergeMode as u32);
+            hash.u32(config.FontBuilderFlags);
+            hash.u32(config.EllipsisChar);
+            let mut ranges = config.GlyphRanges;
+            while !ranges.is_null() && *ranges != 0 {
+                hash.u32(*ranges);
+                ranges = ranges.add(1);
+            }
+            hash.u32(0);
+            hash.u32(font_index(fonts, config.DstFont) as u32);
+        }
+        for rect in slice(atlas.CustomRects.Data, atlas.CustomRects.Size) {
+            hash.u32(rect.Width as u32);
+            hash.u32(rect.Height as u32);
+            hash.u32(rect.GlyphID);
+            hash.f32(rect.GlyphAdvanceX);
+            hash.f32(rect.GlyphOffset.x);
+            hash.f32(rect.GlyphOffset.y);
+            hash.u32(font_index(fonts, rect.Font) as u32);
+        }
+        hash.0
+    }
+}
+
+fn font_index(fonts: &[*mut sys::ImFont], font: *const sys::ImFont) -> i32 {
+    fonts
+        .iter()
+        .position(|f| std::ptr::eq(*f, font))
+        .map_or(-1, |index| index as i32)
+}
+
+struct CachedGlyph {
+    codepoint: u32,
+    colored: bool,
+    /// AdvanceX, X0, Y0, X1, Y1, U0, V0, U1, V1
+    metrics: [f32; 9],
+}
+
+struct CachedFont {
+    size: f32,
+    ascent: f32,
+    descent: f32,
+    fallback_char: u32,
+    ellipsis_char: u32,
+    dot_char: u32,
+    glyphs: Vec<CachedGlyph>,
+}
+
+struct CachedRect {
+    size: [u16; 4],
+    glyph_id: u32,
+    advance_x: f32,
+    offset: [f32; 2],
+    font: i32,
+}
+
+/// 构建完成的图集
+struct AtlasImage {
+    width: i32,
+    height: i32,
+    /// 像素为RGBA32，否则为Alpha8
+    use_colors: bool,
+    uv_scale: [f32; 2],
+    uv_white_pixel: [f32; 2],
+    uv_lines: Vec<[f32; 4]>,
+    pack_id_mouse_cursors: i32,
+    pack_id_lines: i32,
+    rects: Vec<CachedRect>,
+    fonts: Vec<CachedFont>,
+    pixels: Vec<u8>,
+}
+
+impl AtlasImage {
+    unsafe fn capture(atlas: &sys::ImFontAtlas) -> AtlasImage {
+        let fonts = slice(atlas.Fonts.Data, atlas.Fonts.Size);
+        let pixel_count = atlas.TexWidth as usize * atlas.TexHeight as usize;
+        let use_colors = atlas.TexPixelsUseColors && !atlas.TexPixelsRGBA32.is_null();
+        let pixels = if use_colors {
+            slice(atlas.TexPixelsRGBA32 as *const u8, pixel_count as i32 * 4).to_vec()
+        } else {
+            slice(atlas.TexPixelsAlpha8, pixel_count as i32).to_vec()
+        };
+        AtlasImage {
+            width: atlas.TexWidth,
+            height: atlas.TexHeight,
+            use_colors,
+            uv_scale: [atlas.TexUvScale.x, atlas.TexUvScale.y],
+            uv_white_pixel: [atlas.TexUvWhitePixel.x, atlas.TexUvWhitePixel.y],
+            uv_lines: atlas
+                .TexUvLines
+                .iter()
+                .map(|v| [v.x, v.y, v.z, v.w])
+                .collect(),
+            pack_id_mouse_cursors: atlas.PackIdMouseCursors,
+            pack_id_lines: atlas.PackIdLines,
+            rects: slice(atlas.CustomRects.Data, atlas.CustomRects.Size)
+                .iter()
+                .map(|rect| CachedRect {
+                    size: [rect.Width, rect.Height, rect.X, rect.Y],
+                    glyph_id: rect.GlyphID,
+                    advance_x: rect.GlyphAdvanceX,
+                    offset: [rect.GlyphOffset.x, rect.GlyphOffset.y],
+                    font: font_index(fonts, rect.Font),
+                })
+                .collect(),
+            fonts: fonts
+                .iter()
+                .map(|font| {
+                    let font = &**font;
+                    CachedFont {
+                        size: font.FontSize,
+                        ascent: font.Ascent,
+                        descent: font.Descent,
+                        fallback_char: font.FallbackChar,
+                        ellipsis_char: font.EllipsisChar,
+                        dot_char: font.DotChar,
+                        glyphs: slice(font.Glyphs.Data, font.Glyphs.Size)
+                            .iter()
+                            .map(|glyph| CachedGlyph {
+                                codepoint: glyph.Codepoint(),
+                                colored: glyph.Colored() != 0,
+                                metrics: [
+                                    glyph.AdvanceX,
+                                    glyph.X0,
+                                    glyph.Y0,
+                                    glyph.X1,
+                                    glyph.Y1,
+                                    glyph.U0,
+                                    glyph.V0,
+                                    glyph.U1,
+                                    glyph.V1,
+                                ],
+                            })
+                            .collect(),
+                    }
+                })
+                .collect(),
+            pixels,
+        }
+    }
+
+    /// 按构建结束时的状态恢复图集，字体与配置须与保存时一致
+    unsafe fn apply(&self, atlas: &mut sys::ImFontAtlas) {
+        sys::ImFontAtlas_ClearTexData(atlas);
+        atlas.TexWidth = self.width;
+        atlas.TexHeight = self.height;
+        atlas.TexUvScale = sys::ImVec2::new(self.uv_scale[0], self.uv_scale[1]);
+        atlas.TexUvWhitePixel = sys::ImVec2::new(self.uv_white_pixel[0], self.uv_white_pixel[1]);
+        for (dst, src) in atlas.TexUvLines.iter_mut().zip(&self.uv_lines) {
+            *dst = sys::ImVec4::new(src[0], src[1], src[2], src[3]);
+        }
+        let pixels = sys::igMemAlloc(self.pixels.len()) as *mut u8;
+        std::ptr::copy_nonoverlapping(self.pixels.as_ptr(), pixels, self.pixels.len());
+        atlas.TexPixelsUseColors = self.use_colors;
+        if self.use_colors {
+            atlas.TexPixelsRGBA32 = pixels as *mut u32;
+        } else {
+            atlas.TexPixelsAlpha8 = pixels;
+        }
+
+        let fonts = slice(atlas.Fonts.Data, atlas.Fonts.Size).to_vec();
+        atlas.CustomRects.Size = 0;
+        for rect in &self.rects {
+            let index = sys::ImFontAtlas_AddCustomRectRegular(
+                atlas,
+                rect.size[0] as i32,
+                rect.size[1] as i32,
+            );
+            let dst = &mut *atlas.CustomRects.Data.add(index as usize);
+            dst.X = rect.size[2];
+            dst.Y = rect.size[3];
+            dst.GlyphID = rect.glyph_id;
+            dst.GlyphAdvanceX = rect.advance_x;
+            dst.GlyphOffset = sys::ImVec2::new(rect.offset[0], rect.offset[1]);
+            dst.Font = fonts
+                .get(rect.font as usize)
+                .copied()
+                .unwrap_or(std::ptr::null_mut());
+        }
+        atlas.PackIdMouseCursors = self.pack_id_mouse_cursors;
+        atlas.PackIdLines = self.pack_id_lines;
+
+        let configs = slice(atlas.ConfigData.Data, atlas.ConfigData.Size);
+        for (&font, cached) in fonts.iter().zip(&self.fonts) {
+            // 与ImFontAtlasBuildSetupFont相同
+            let sources: Vec<&sys::ImFontConfig> = configs
+                .iter()
+                .filter(|config| config.DstFont == font)
+                .collect();
+            let font = &mut *font;
+            font.ContainerAtlas = atlas as *mut sys::ImFontAtlas;
+            font.ConfigData = sources.first().map_or(std::ptr::null(), |config| *config);
+            font.ConfigDataCount = sources.len() as i16;
+            font.FontSize = cached.size;
+            font.Ascent = cached.ascent;
+            font.Descent = cached.descent;
+            font.FallbackChar = cached.fallback_char;
+            font.EllipsisChar = cached.ellipsis_char;
+            font.DotChar = cached.dot_char;
+            for glyph in &cached.glyphs {
+                let [advance_x, x0, y0, x1, y1, u0, v0, u1, v1] = glyph.metrics;
+                sys::ImFont_AddGlyph(
+                    font,
+                    std::ptr::null(),
+                    glyph.codepoint,
+                    x0,
+                    y0,
+                    x1,
+                    y1,
+                    u0,
+                    v0,
+                    u1,
+                    v1,
+                    advance_x,
+                );
+                let last = font.Glyphs.Size as usize - 1;
+                (*font.Glyphs.Data.add(last)).set_Colored(glyph.colored as u32);
+            }
+            sys::ImFont_BuildLookupTable(font);
+        }
+        atlas.TexReady = true;
+    }
+
+    fn write(&self, out: &mut Vec<u8>) -> io::Result<()> {
+        for value in [self.width, self.height] {
+            out.write_all(&value.to_le_bytes())?;
+        }
+        out.write_all(&[self.use_colors as u8])?;
+        let floats = self
+            .uv_scale
+            .iter()
+            .chain(&self.uv_white_pixel)
+            .chain(self.uv_lines.iter().flatten());
+        out.write_all(&(self.uv_lines.len() as u32).to_le_bytes())?;
+        for value in floats {
+            out.write_all(&value.to_le_bytes())?;
+        }
+        for value in [self.pack_id_mouse_cursors, self.pack_id_lines] {
+            out.write_all(&value.to_le_bytes())?;
+        }
+        out.write_all(&(self.rects.len() as u32).to_le_bytes())?;
+        for rect in &self.rects {
+            for value in rect.size {
+                out.write_all(&value.to_le_bytes())?;
+            }
+            out.write_all(&rect.glyph_id.to_le_bytes())?;
+            for value in [rect.advance_x, rect.offset[0], rect.offset[1]] {
+                out.write_all(&value.to_le_bytes())?;
+            }
+            out.write_all(&rect.font.to_le_bytes())?;
+        }
+        out.write_all(&(self.fonts.len() as u32).to_le_bytes())?;
+        for font in &self.fonts {
+            for value in [font.size, font.ascent, font.descent] {
+                out.write_all(&value.to_le_bytes())?;
+            }
+            for value in [font.fallback_char, font.ellipsis_char, font.dot_char] {
+                out.write_all(&value.to_le_bytes())?;
+            }
+            out.write_all(&(font.glyphs.len() as u32).to_le_bytes())?;
+            for glyph in &font.glyphs {
+                out.write_all(&glyph.codepoint.to_le_bytes())?;
+                out.write_all(&[glyph.colored as u8])?;
+                for value in glyph.metrics {
+                    out.write_all(&value.to_le_bytes())?;
+                }
+            }
+        }
+        out.write_all(&(self.pixels.len() as u64).to_le_bytes())?;
+        out.write_all(&self.pixels)
+    }
+
+    fn read(reader: &mut &[u8]) -> io::Result<AtlasImage> {
+        let width = read_u32(reader)? as i32;
+        let height = read_u32(reader)? as i32;
+        let use_colors = read_u8(reader)? != 0;
+        let line_count = read_u32(reader)? as usize;
+        let uv_scale = [read_f32(reader)?, read_f32(reader)?];
+        let uv_white_pixel = [read_f32(reader)?, read_f32(reader)?];
+        let uv_lines = (0..line_count)
+            .map(|_| {
+                Ok([
+                    read_f32(reader)?,
+                    read_f32(reader)?,
+                    read_f32(reader)?,
+                    read_f32(reader)?,
+                ])
+            })
+            .collect::<io::Result<_>>()?;
+        let pack_id_mouse_cursors = read_u32(reader)? as i32;
+        let pack_id_lines = read_u32(reader)? as i32;
+        let rect_count = read_u32(reader)?;
+        let rects = (0..rect_count)
+            .map(|_| {
+                Ok(CachedRect {
+                    size: [
+                        read_u16(reader)?,
+                        read_u16(reader)?,
+                        read_u16(reader)?,
+                        read_u16(reader)?,
+                    ],
+                    glyph_id: read_u32(reader)?,
+                    advance_x: read_f32(reader)?,
+                    offset: [read_f32(reader)?, read_f32(reader)?],
+                    font: read_u32(reader)? as i32,
+                })
+            })
+            .collect::<io::Result<_>>()?;
+        let font_count = read_u32(reader)?;
+        let fonts = (0..font_count)
+            .map(|_| {
+                let size = read_f32(reader)?;
+                let ascent = read_f32(reader)?;
+                let descent = read_f32(reader)?;
+                let fallback_char = read_u32(reader)?;
+                let ellipsis_char = read_u32(reader)?;
+                let dot_char = read_u32(reader)?;
+                let glyph_count = read_u32(reader)?;
+                let glyphs = (0..glyph_count)
+                    .map(|_| {
+                        let codepoint = read_u32(reader)?;
+                        let colored = read_u8(reader)? != 0;
+                        let mut metrics = [0.0; 9];
+                        for value in &mut metrics {
+                            *value = read_f32(reader)?;
+                        }
+                        Ok(CachedGlyph {
+                            codepoint,
+                            colored,
+                            metrics,
+                        })
+                    })
+                    .collect::<io::Result<_>>()?;
+                Ok(CachedFont {
+                    size,
+                    ascent,
+                    descent,
+                    fallback_char,
+                    ellipsis_char,
+                    dot_char,
+                    glyphs,
+                })
+            })
+            .collect::<io::Result<_>>()?;
+        let pixel_len = read_u64(reader)? as usize;
+        let bytes_per_pixel = if use_colors { 4 } else { 1 };
+        if pixel_len != width as usize * height as usize * bytes_per_pixel
+            || reader.len() != pixel_len
+        {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "truncated font atlas",
+            ));
+        }
+        Ok(AtlasImage {
+            width,
+            height,
+            use_colors,
+            uv_scale,
+            uv_white_pixel,
+            uv_lines,
+            pack_id_mouse_cursors,
+            pack_id_lines,
+            rects,
+            fonts,
+            pixels: reader.to_vec(),
+        })
+    }
+}
+
+fn read_u8(reader: &mut &[u8]) -> io::Result<u8> {
+    let mut bytes = [0; 1];
+    reader.read_exact(&mut bytes)?;
+    Ok(bytes[0])
+}
+
+fn read_u16(reader: &mut &[u8]) -> io::Result<u16> {
+    let mut bytes = [0; 2];
+    reader.read_exact(&mut bytes)?;
+    Ok(u16::from_le_bytes(bytes))
+}
+
+fn read_u32(reader: &mut &[u8]) -> io::Result<u32> {
+    let mut bytes = [0; 4];
+    reader.read_exact(&mut bytes)?;
+    Ok(u32::from_le_bytes(bytes))
+}
+
+fn read_u64(reader: &mut &[u8]) -> io::Result<u64> {
+    let mut bytes = [0; 8];
+    reader.read_exact(&mut bytes)?;
+    Ok(u64::from_le_bytes(bytes))
+}
+
+fn read_f32(reader: &mut &[u8]) -> io::Result<f32> {
+    read_u32(reader).map(f32::from_bits)
+}
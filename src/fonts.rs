@@ -0,0 +1,635 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fs::{self, File};
+use std::io::{self, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+use imgui::{Context, FontConfig, FontGlyphRanges, FontId, FontSource};
+
+use crate::glyphs::DynamicGlyphs;
+use crate::icons::{self, IconFont};
+
+/// 常用符号：标点、箭头、数学符号、几何图形、杂项符号
+static SYMBOL_RANGES: [u32; 15] = [
+    0x2000, 0x206F, 0x2190, 0x21FF, 0x2200, 0x22FF, 0x2300, 0x23FF, 0x2460, 0x24FF, 0x25A0, 0x27BF,
+    0x2B00, 0x2BFF, 0,
+];
+
+/// 表情：杂项符号、装饰符号、补充平面的表情与符号
+static EMOJI_RANGES: [u32; 7] = [0x2600, 0x27BF, 0x1F000, 0x1FAFF, 0xFE0F, 0xFE0F, 0];
+
+/// ImGuiFreeTypeBuilderFlags_LoadColor，光栅化彩色(COLR/CPAL)字形
+#[cfg(feature = "freetype")]
+const COLOR_BUILDER_FLAGS: u32 = 1 << 8;
+/// stb_truetype不支持彩色字形，使用字体中的单色轮廓
+#[cfg(not(feature = "freetype"))]
+const COLOR_BUILDER_FLAGS: u32 = 0;
+
+/// 字体的字形范围
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum GlyphRanges {
+    /// 基本拉丁字母与拉丁补充
+    Default,
+    ChineseSimplifiedCommon,
+    ChineseFull,
+    Japanese,
+    Korean,
+    Cyrillic,
+    Symbols,
+    Emoji,
+    /// 成对的起止码点，以0结尾
+    Custom(&'static [u32]),
+    /// 按需加载，只加入[DynamicGlyphs]中登记过的字符，适合中日韩等大字符集
+    Dynamic,
+    /// 按需加载的表情，与[GlyphRanges::Dynamic]的字体不互为备选
+    DynamicEmoji,
+}
+
+impl GlyphRanges {
+    fn to_imgui(&self, glyphs: &DynamicGlyphs) -> FontGlyphRanges {
+        match self {
+            GlyphRanges::Default => FontGlyphRanges::default(),
+            GlyphRanges::ChineseSimplifiedCommon => FontGlyphRanges::chinese_simplified_common(),
+            GlyphRanges::ChineseFull => FontGlyphRanges::chinese_full(),
+            GlyphRanges::Japanese => FontGlyphRanges::japanese(),
+            GlyphRanges::Korean => FontGlyphRanges::korean(),
+            GlyphRanges::Cyrillic => FontGlyphRanges::cyrillic(),
+            GlyphRanges::Symbols => FontGlyphRanges::from_slice(&SYMBOL_RANGES),
+            GlyphRanges::Emoji => FontGlyphRanges::from_slice(&EMOJI_RANGES),
+            GlyphRanges::Custom(ranges) => FontGlyphRanges::from_slice(ranges),
+            GlyphRanges::Dynamic | GlyphRanges::DynamicEmoji => glyphs.imgui_ranges(),
+        }
+    }
+
+    /// 是否按需加载
+    pub fn is_dynamic(&self) -> bool {
+        matches!(self, GlyphRanges::Dynamic | GlyphRanges::DynamicEmoji)
+    }
+}
+
+/// 回退链中的一个字体
+#[derive(Clone, Debug, PartialEq)]
+pub struct FontFace {
+    /// 字体族名，例如`Microsoft YaHei`或`微软雅黑`，不区分大小写
+    pub family: String,
+    pub ranges: GlyphRanges,
+    /// 彩色字形，需开启`freetype`特性
+    pub color: bool,
+}
+
+impl FontFace {
+    pub fn new(family: impl Into<String>, ranges: GlyphRanges) -> FontFace {
+        FontFace {
+            family: family.into(),
+            ranges,
+            color: false,
+        }
+    }
+
+    /// 彩色表情字体
+    pub fn emoji(family: impl Into<String>) -> FontFace {
+        FontFace {
+            family: family.into(),
+            ranges: GlyphRanges::Emoji,
+            color: true,
+        }
+    }
+}
+
+/// 一个字体角色的回退链，字形范围相同的字体互为备选，各范围找到的字体合并为一个imgui字体
+#[derive(Clone, Debug, PartialEq)]
+pub struct FontSpec {
+    pub faces: Vec<FontFace>,
+    /// 未缩放的字号
+    pub size: f32,
+}
+
+impl FontSpec {
+    /// 拉丁字体 + 中文字体 + 符号字体
+    pub fn with_fallbacks(latin: &[&str], size: f32) -> FontSpec {
+        let mut faces: Vec<FontFace> = latin
+            .iter()
+            .map(|family| FontFace::new(*family, GlyphRanges::Default))
+            .collect();
+        for family in [
+            "Microsoft YaHei",
+            "SimHei",
+            "Noto Sans CJK SC",
+            "PingFang SC",
+        ] {
+            faces.push(FontFace::new(family, GlyphRanges::ChineseSimplifiedCommon));
+        }
+        for family in ["Segoe UI Symbol", "DejaVu Sans"] {
+            faces.push(FontFace::new(family, GlyphRanges::Symbols));
+        }
+        FontSpec { faces, size }
+    }
+
+    /// 加入表情字体，Windows使用Segoe UI Emoji(COLR)。
+    /// Noto Color Emoji等位图(CBDT)字体只能以内置的位图尺寸光栅化。
+    /// 表情字体放在符号字体之前，重叠的杂项符号使用彩色字形
+    pub fn with_emoji(mut self) -> FontSpec {
+        let index = self
+            .faces
+            .iter()
+            .position(|face| face.ranges == GlyphRanges::Symbols)
+            .unwrap_or(self.faces.len());
+        let emoji = ["Segoe UI Emoji", "Noto Color Emoji", "Apple Color Emoji"]
+            .into_iter()
+            .map(FontFace::emoji);
+        self.faces.splice(index..index, emoji);
+        self
+    }
+
+    /// 中日韩与表情字体改为按需加载
+    pub fn with_dynamic_glyphs(mut self) -> FontSpec {
+        for face in &mut self.faces {
+            match face.ranges {
+                GlyphRanges::ChineseSimplifiedCommon
+                | GlyphRanges::ChineseFull
+                | GlyphRanges::Japanese
+                | GlyphRanges::Korean => face.ranges = GlyphRanges::Dynamic,
+                GlyphRanges::Emoji => face.ranges = GlyphRanges::DynamicEmoji,
+                _ => {}
+            }
+        }
+        self
+    }
+}
+
+/// 字体角色
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum FontRole {
+    /// 正文，同时是imgui的默认字体
+    Body,
+    Title,
+    /// 等宽
+    Mono,
+}
+
+/// 各字体角色的配置
+#[derive(Clone, Debug, PartialEq)]
+pub struct FontOptions {
+    pub body: FontSpec,
+    pub title: FontSpec,
+    pub mono: FontSpec,
+    /// 按名称加入的其它字体，通过[crate::FrameContext::named_font]使用
+    pub extra: BTreeMap<String, FontSpec>,
+    /// 合并到所有字体中的图标字体，通过[crate::Icon]使用
+    pub icons: Option<IconFont>,
+}
+
+/// 只有正文预先加载常用中文字形，标题与等宽字体的中文按需加载，
+/// 需要时用[crate::FrameContext::request_glyphs]登记，避免图集中有三份中文字形
+impl Default for FontOptions {
+    fn default() -> FontOptions {
+        FontOptions {
+            body: FontSpec::with_fallbacks(&["Segoe UI", "Arial"], 14.0),
+            title: FontSpec::with_fallbacks(&["Segoe UI Semibold", "Segoe UI", "Arial"], 18.0)
+                .with_dynamic_glyphs(),
+            mono: FontSpec::with_fallbacks(&["Consolas", "Cascadia Mono", "Courier New"], 14.0)
+                .with_dynamic_glyphs(),
+            extra: BTreeMap::new(),
+            icons: None,
+        }
+    }
+}
+
+impl FontOptions {
+    pub fn spec(&self, role: FontRole) -> &FontSpec {
+        match role {
+            FontRole::Body => &self.body,
+            FontRole::Title => &self.title,
+            FontRole::Mono => &self.mono,
+        }
+    }
+
+    pub fn spec_mut(&mut self, role: FontRole) -> &mut FontSpec {
+        match role {
+            FontRole::Body => &mut self.body,
+            FontRole::Title => &mut self.title,
+            FontRole::Mono => &mut self.mono,
+        }
+    }
+
+    /// 所有字体的中日韩与表情字形改为按需加载，见[FontSpec::with_dynamic_glyphs]
+    pub fn with_dynamic_glyphs(self) -> FontOptions {
+        self.map_specs(FontSpec::with_dynamic_glyphs)
+    }
+
+    /// 所有字体加入表情字体，见[FontSpec::with_emoji]
+    pub fn with_emoji(self) -> FontOptions {
+        self.map_specs(FontSpec::with_emoji)
+    }
+
+    fn map_specs(self, f: impl Fn(FontSpec) -> FontSpec) -> FontOptions {
+        FontOptions {
+            body: f(self.body),
+            title: f(self.title),
+            mono: f(self.mono),
+            extra: self
+                .extra
+                .into_iter()
+                .map(|(name, spec)| (name, f(spec)))
+                .collect(),
+            icons: self.icons,
+        }
+    }
+
+    /// 是否有字体或图标字体按需加载，见[GlyphRanges::is_dynamic]。
+    /// 为true时按需加载字符后须重建图集，否则图集仍引用旧的字形范围
+    pub fn uses_dynamic_glyphs(&self) -> bool {
+        [&self.body, &self.title, &self.mono]
+            .into_iter()
+            .chain(self.extra.values())
+            .flat_map(|spec| &spec.faces)
+            .map(|face| &face.ranges)
+            .chain(self.icons.iter().map(|icons| &icons.ranges))
+            .any(GlyphRanges::is_dynamic)
+    }
+}
+
+/// 各字体角色在字体图集中的字体，字体图集重建后失效
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FontRoles {
+    pub body: FontId,
+    pub title: FontId,
+    pub mono: FontId,
+    pub extra: HashMap<String, FontId>,
+}
+
+impl FontRoles {
+    /// 所有角色使用同一字体
+    pub fn uniform(font: FontId) -> FontRoles {
+        FontRoles {
+            body: font,
+            title: font,
+            mono: font,
+            extra: HashMap::new(),
+        }
+    }
+
+    pub fn get(&self, role: FontRole) -> FontId {
+        match role {
+            FontRole::Body => self.body,
+            FontRole::Title => self.title,
+            FontRole::Mono => self.mono,
+        }
+    }
+
+    /// [FontOptions::extra]中的字体
+    pub fn named(&self, name: &str) -> Option<FontId> {
+        self.extra.get(name).copied()
+    }
+}
+
+/// 系统字体目录
+pub fn system_font_dirs() -> Vec<PathBuf> {
+    let mut dirs = Vec::new();
+    let home = |path: &str| std::env::var_os("HOME").map(|home| Path::new(&home).join(path));
+    if cfg!(windows) {
+        let windir = std::env::var_os("WINDIR").unwrap_or_else(|| r"C:\Windows".into());
+        dirs.push(Path::new(&windir).join("Fonts"));
+        // 仅为当前用户安装的字体
+        if let Some(local) = std::env::var_os("LOCALAPPDATA") {
+            dirs.push(Path::new(&local).join(r"Microsoft\Windows\Fonts"));
+        }
+    } else if cfg!(target_os = "macos") {
+        dirs.push("/System/Library/Fonts".into());
+        dirs.push("/Library/Fonts".into());
+        dirs.extend(home("Library/Fonts"));
+    } else {
+        dirs.push("/usr/share/fonts".into());
+        dirs.push("/usr/local/share/fonts".into());
+        dirs.extend(home(".local/share/fonts"));
+        dirs.extend(home(".fonts"));
+    }
+    dirs
+}
+
+/// 按字体族名查找系统字体，并将找到的字体按角色加入imgui
+pub struct FontManager {
+    dirs: Vec<PathBuf>,
+    /// 小写字体族名 -> (字体文件, 是否为常规字重)，首次查找时建立
+    index: Option<HashMap<String, (PathBuf, bool)>>,
+    data: HashMap<PathBuf, Rc<Vec<u8>>>,
+    glyphs: DynamicGlyphs,
+}
+
+impl Default for FontManager {
+    fn default() -> FontManager {
+        FontManager::with_dirs(system_font_dirs())
+    }
+}
+
+impl FontManager {
+    /// 搜索系统字体目录
+    pub fn new() -> FontManager {
+        FontManager::default()
+    }
+
+    /// 搜索指定目录
+    pub fn with_dirs(dirs: Vec<PathBuf>) -> FontManager {
+        FontManager {
+            dirs,
+            index: None,
+            data: HashMap::new(),
+            glyphs: DynamicGlyphs::new(),
+        }
+    }
+
+    pub fn dirs(&self) -> &[PathBuf] {
+        &self.dirs
+    }
+
+    /// 按需加载的字符集
+    pub fn glyphs(&self) -> &DynamicGlyphs {
+        &self.glyphs
+    }
+
+    pub fn glyphs_mut(&mut self) -> &mut DynamicGlyphs {
+        &mut self.glyphs
+    }
+
+    /// 替换按需加载的字符集，例如使用缓存文件，之后须重建字体图集
+    pub fn set_glyphs(&mut self, glyphs: DynamicGlyphs) {
+        self.glyphs = glyphs;
+    }
+
+    /// 按字体族名查找字体文件，不区分大小写
+    pub fn find(&mut self, family: &str) -> Option<PathBuf> {
+        let dirs = &self.dirs;
+        let index = self.index.get_or_insert_with(|| build_index(dirs));
+        index
+            .get(&family.to_lowercase())
+            .map(|(path, _)| path.clone())
+    }
+
+    /// 读取字体文件，读取过的文件会被缓存
+    pub fn load(&mut self, family: &str) -> Option<Rc<Vec<u8>>> {
+        let path = self.find(family)?;
+        self.read(path)
+    }
+
+    fn read(&mut self, path: PathBuf) -> Option<Rc<Vec<u8>>> {
+        if let Some(data) = self.data.get(&path) {
+            return Some(data.clone());
+        }
+        match fs::read(&path) {
+            Ok(data) => {
+                let data = Rc::new(data);
+                self.data.insert(path, data.clone());
+                Some(data)
+            }
+            Err(e) => {
+                log::warn!("Failed to read font {}: {e}", path.display());
+                None
+            }
+        }
+    }
+
+    /// 按回退链加入一个字体，链中的字体都找不到时使用内置字体
+    pub fn add_font(&mut self, imgui: &mut Context, spec: &FontSpec, scale: f32) -> FontId {
+        self.add_font_with_icons(imgui, spec, None, scale)
+    }
+
+    /// 按回退链加入一个字体并合并图标字体
+    pub fn add_font_with_icons(
+        &mut self,
+        imgui: &mut Context,
+        spec: &FontSpec,
+        icons: Option<&IconFont>,
+        scale: f32,
+    ) -> FontId {
+        let size_pixels = spec.size * scale;
+        // 相同字形范围的字体互为备选，只使用找到的第一个
+        let mut faces: Vec<(Rc<Vec<u8>>, &FontFace)> = Vec::new();
+        for face in &spec.faces {
+            if faces.iter().any(|(_, found)| found.ranges == face.ranges) {
+                continue;
+            }
+            if let Some(data) = self.load(&face.family) {
+                faces.push((data, face));
+            }
+        }
+        let icons = icons.and_then(|icons| Some((self.load_icons(icons)?, icons)));
+        let mut sources: Vec<FontSource> = if faces.is_empty() {
+            log::warn!(
+                "No font found for {:?}, using the embedded font",
+                spec.faces
+            );
+            vec![FontSource::DefaultFontData {
+                config: Some(FontConfig {
+                    size_pixels,
+                    ..FontConfig::default()
+                }),
+            }]
+        } else {
+            faces
+                .iter()
+                .map(|(data, face)| FontSource::TtfData {
+                    data,
+                    size_pixels,
+                    config: Some(FontConfig {
+                        glyph_ranges: face.ranges.to_imgui(&self.glyphs),
+                        oversample_h: 2,
+                        font_builder_flags: if face.color { COLOR_BUILDER_FLAGS } else { 0 },
+                        name: Some(format!("{} {}px", face.family, size_pixels)),
+                        ..FontConfig::default()
+                    }),
+                })
+                .collect()
+        };
+        if let Some((data, icons)) = &icons {
+            let icon_size = size_pixels * icons.size_ratio;
+            let [x, y] = icons.glyph_offset;
+            sources.push(FontSource::TtfData {
+                data,
+                size_pixels: icon_size,
+                config: Some(FontConfig {
+                    glyph_ranges: icons.ranges.to_imgui(&self.glyphs),
+                    glyph_offset: [(x * icon_size).round(), (y * icon_size).round()],
+                    glyph_min_advance_x: if icons.monospace { icon_size } else { 0.0 },
+                    pixel_snap_h: true,
+                    name: Some(format!("{:?} {}px", icons.set, icon_size)),
+                    ..FontConfig::default()
+                }),
+            });
+        }
+        imgui.fonts().add_font(&sources)
+    }
+
+    /// 图标字体优先按文件读取
+    fn load_icons(&mut self, icons: &IconFont) -> Option<Rc<Vec<u8>>> {
+        if icons.path.is_file() {
+            return self.read(icons.path.clone());
+        }
+        let data = self.load(&icons.path.to_string_lossy());
+        if data.is_none() {
+            log::warn!("Icon font {} not found", icons.path.display());
+        }
+        data
+    }
+
+    /// 加入所有字体角色，正文字体最先加入，作为imgui的默认字体
+    pub fn build(&mut self, imgui: &mut Context, options: &FontOptions, scale: f32) -> FontRoles {
+        let icons = options.icons.as_ref();
+        icons::set_icon_set(icons.map(|icons| icons.set).unwrap_or_default());
+        let mut roles = FontRoles {
+            body: self.add_font_with_icons(imgui, &options.body, icons, scale),
+            title: self.add_font_with_icons(imgui, &options.title, icons, scale),
+            mono: self.add_font_with_icons(imgui, &options.mono, icons, scale),
+            extra: HashMap::new(),
+        };
+        self.add_extra(imgui, options, scale, &mut roles);
+        roles
+    }
+
+    /// 加入[FontOptions::extra]中的字体
+    pub fn add_extra(
+        &mut self,
+        imgui: &mut Context,
+        options: &FontOptions,
+        scale: f32,
+        roles: &mut FontRoles,
+    ) {
+        for (name, spec) in &options.extra {
+            let font = self.add_font_with_icons(imgui, spec, options.icons.as_ref(), scale);
+            roles.extra.insert(name.clone(), font);
+        }
+    }
+}
+
+/// 扫描字体目录，读取每个字体文件的字体族名，同一字体族优先使用常规字重
+fn build_index(dirs: &[PathBuf]) -> HashMap<String, (PathBuf, bool)> {
+    let mut index = HashMap::new();
+    let mut pending: Vec<PathBuf> = dirs.to_vec();
+    while let Some(dir) = pending.pop() {
+        let Ok(entries) = fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.is_dir() {
+                pending.push(path);
+                continue;
+            }
+            let extension = path
+                .extension()
+                .and_then(|extension| extension.to_str())
+                .map(|extension| extension.to_ascii_lowercase());
+            if !matches!(extension.as_deref(), Some("ttf" | "otf" | "ttc")) {
+                continue;
+            }
+            match family_names(&path) {
+                Ok(names) => {
+                    for (name, regular) in names {
+                        let entry = index
+                            .entry(name.to_lowercase())
+                            .or_insert((path.clone(), regular));
+                        if regular && !entry.1 {
+                            *entry = (path.clone(), regular);
+                        }
+                    }
+                }
+                Err(e) => log::debug!("Skipping font {}: {e}", path.display()),
+            }
+        }
+    }
+    index
+}
+
+fn read_u16(data: &[u8], offset: usize) -> io::Result<u16> {
+    data.get(offset..offset + 2)
+        .map(|bytes| u16::from_be_bytes([bytes[0], bytes[1]]))
+        .ok_or_else(|| io::Error::new(io::ErrorKind::UnexpectedEof, "truncated font table"))
+}
+
+fn read_u32(data: &[u8], offset: usize) -> io::Result<u32> {
+    Ok((read_u16(data, offset)? as u32) << 16 | read_u16(data, offset + 2)? as u32)
+}
+
+fn read_at(file: &mut File, offset: u64, len: usize) -> io::Result<Vec<u8>> {
+    let mut buffer = vec![0; len];
+    file.seek(SeekFrom::Start(offset))?;
+    file.read_exact(&mut buffer)?;
+    Ok(buffer)
+}
+
+/// 从name表读取字体族名(各语言)及是否为常规字重，集合字体只读取第一个字体，imgui只能加载该字体
+fn family_names(path: &Path) -> io::Result<Vec<(String, bool)>> {
+    let mut file = File::open(path)?;
+    let header = read_at(&mut file, 0, 12)?;
+    let font_offset = if &header[0..4] == b"ttcf" {
+        read_u32(&header, 8)? as u64
+    } else {
+        0
+    };
+    let header = read_at(&mut file, font_offset, 12)?;
+    let num_tables = read_u16(&header, 4)? as usize;
+    let records = read_at(&mut file, font_offset + 12, num_tables * 16)?;
+    let name_record = (0..num_tables)
+        .map(|index| index * 16)
+        .find(|offset| &records[*offset..*offset + 4] == b"name")
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "missing name table"))?;
+    let table_offset = read_u32(&records, name_record + 8)? as u64;
+    let table_len = read_u32(&records, name_record + 12)? as usize;
+    let table = read_at(&mut file, table_offset, table_len)?;
+    let count = read_u16(&table, 2)? as usize;
+    let strings = read_u16(&table, 4)? as usize;
+    // 按名称编号收集：1字体族名，2子族名，16排版用字体族名，17排版用子族名
+    let mut names: HashMap<u16, Vec<String>> = HashMap::new();
+    for index in 0..count {
+        let record = 6 + index * 12;
+        let platform = read_u16(&table, record)?;
+        let encoding = read_u16(&table, record + 2)?;
+        let name_id = read_u16(&table, record + 6)?;
+        let len = read_u16(&table, record + 8)? as usize;
+        let offset = strings + read_u16(&table, record + 10)? as usize;
+        if !matches!(name_id, 1 | 2 | 16 | 17) {
+            continue;
+        }
+        let Some(bytes) = table.get(offset..offset + len) else {
+            continue;
+        };
+        let name = match (platform, encoding) {
+            // Unicode与Windows平台为UTF-16BE
+            (0, _) | (3, 1) | (3, 10) => {
+                let units: Vec<u16> = bytes
+                    .chunks_exact(2)
+                    .map(|unit| u16::from_be_bytes([unit[0], unit[1]]))
+                    .collect();
+                String::from_utf16_lossy(&units)
+            }
+            (1, 0) => bytes.iter().map(|b| *b as char).collect(),
+            _ => continue,
+        };
+        let names = names.entry(name_id).or_default();
+        if !name.is_empty() && !names.contains(&name) {
+            names.push(name);
+        }
+    }
+    let is_regular = |id: u16| {
+        names.get(&id).is_some_and(|names| {
+            names.iter().any(|name| {
+                matches!(
+                    name.to_ascii_lowercase().as_str(),
+                    "regular" | "book" | "normal" | "roman"
+                )
+            })
+        })
+    };
+    let regular = is_regular(2);
+    let typographic_regular = if names.contains_key(&17) {
+        is_regular(17)
+    } else {
+        regular
+    };
+    let mut families = Vec::new();
+    for (id, regular) in [(1, regular), (16, typographic_regular)] {
+        for name in names.get(&id).into_iter().flatten() {
+            families.push((name.clone(), regular));
+        }
+    }
+    Ok(families)
+}
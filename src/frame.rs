@@ -0,0 +1,220 @@
+use std::ops::{Deref, DerefMut};
+
+use imgui::{FontId, Style, Ui};
+
+use crate::canvas::CanvasMapping;
+use crate::fonts::{FontOptions, FontRole, FontRoles, FontSpec};
+use crate::glyphs::DynamicGlyphs;
+use crate::hit_test::{HitRegions, Region};
+use crate::hotkey::Hotkeys;
+use crate::theme::Theme;
+use crate::window::OverlayMode;
+
+/// 每帧传给渲染函数的上下文
+pub struct FrameContext<'a> {
+    pub(crate) style: Style,
+    pub(crate) requested_style: Option<Style>,
+    pub(crate) visible: bool,
+    pub(crate) requested_visible: Option<bool>,
+    pub(crate) canvas: CanvasMapping,
+    pub(crate) dpi_scale: f32,
+    pub(crate) fonts: &'a FontRoles,
+    pub(crate) font_options: &'a FontOptions,
+    pub(crate) requested_fonts: Option<FontOptions>,
+    pub(crate) glyphs: &'a mut DynamicGlyphs,
+    pub(crate) requested_theme: Option<Theme>,
+    pub(crate) hotkeys: &'a mut Hotkeys,
+    pub(crate) hit_regions: &'a mut HitRegions,
+    pub(crate) mode: OverlayMode,
+    pub(crate) base_mode: OverlayMode,
+    pub(crate) requested_mode: Option<OverlayMode>,
+}
+
+impl FrameContext<'_> {
+    pub(crate) fn requested_mode(&self) -> Option<OverlayMode> {
+        self.requested_mode
+    }
+
+    pub(crate) fn requested_visible(&self) -> Option<bool> {
+        self.requested_visible
+    }
+
+    pub(crate) fn take_requested_fonts(&mut self) -> Option<FontOptions> {
+        self.requested_fonts.take()
+    }
+
+    pub(crate) fn take_requested_theme(&mut self) -> Option<Theme> {
+        self.requested_theme.take()
+    }
+
+    pub(crate) fn take_requested_style(&mut self) -> Option<Style> {
+        self.requested_style.take()
+    }
+
+    /// 样式，尺寸为DPI缩放前的值，包含本帧尚未生效的修改。通过[StyleHandle]修改时在下一帧开始前生效
+    pub fn style(&mut self) -> StyleHandle<'_> {
+        StyleHandle {
+            current: &self.style,
+            requested: &mut self.requested_style,
+        }
+    }
+
+    /// 替换样式，尺寸为DPI缩放前的值，下一帧开始前生效
+    pub fn set_style(&mut self, style: Style) {
+        self.requested_style = Some(style);
+    }
+
+    /// 修改样式，下一帧开始前生效
+    pub fn edit_style(&mut self, edit: impl FnOnce(&mut Style)) {
+        edit(&mut self.style());
+    }
+
+    /// 覆盖内容是否显示，包含本帧尚未生效的修改
+    pub fn is_visible(&self) -> bool {
+        self.requested_visible.unwrap_or(self.visible)
+    }
+
+    /// 显示或隐藏覆盖内容，本帧结束后开始淡入淡出
+    pub fn set_visible(&mut self, visible: bool) {
+        self.requested_visible = Some(visible);
+    }
+
+    /// 切换主题，本帧结束后生效。主题替换整个样式，本帧内对样式的修改都会被丢弃
+    pub fn set_theme(&mut self, theme: Theme) {
+        self.requested_style = None;
+        self.requested_theme = Some(theme);
+    }
+
+    /// 虚拟画布映射，未设置虚拟画布时为等比映射
+    pub fn canvas(&self) -> &CanvasMapping {
+        &self.canvas
+    }
+
+    /// 覆盖窗口大小
+    pub fn overlay_size(&self) -> [f32; 2] {
+        self.canvas.overlay_size()
+    }
+
+    /// 目标所在显示器的DPI缩放比例
+    pub fn dpi_scale(&self) -> f32 {
+        self.dpi_scale
+    }
+
+    /// 字体角色对应的字体，配合`ui.push_font`使用。
+    /// 默认配置下标题与等宽字体的中文按需加载，绘制前需用[FrameContext::request_glyphs]登记
+    pub fn font(&self, role: FontRole) -> FontId {
+        self.fonts.get(role)
+    }
+
+    pub fn fonts(&self) -> &FontRoles {
+        self.fonts
+    }
+
+    /// 按名称加入的字体，见[FontOptions::extra]
+    pub fn named_font(&self, name: &str) -> Option<FontId> {
+        self.fonts.named(name)
+    }
+
+    /// 登记将要绘制的文本，使用[crate::GlyphRanges::Dynamic]时缺失的字符在本帧结束后加入字体图集
+    pub fn request_glyphs(&mut self, text: &str) {
+        self.glyphs.request(text);
+    }
+
+    /// 当前的字体配置，包含本帧尚未生效的修改
+    pub fn font_options(&self) -> &FontOptions {
+        self.requested_fonts.as_ref().unwrap_or(self.font_options)
+    }
+
+    /// 替换字体配置，本帧结束后重建字体图集，之前取得的[FontId]随之失效
+    pub fn set_font_options(&mut self, options: FontOptions) {
+        // 改回当前配置时不需要重建
+        self.requested_fonts = (options != *self.font_options).then_some(options);
+    }
+
+    /// 修改字体配置，本帧结束后重建字体图集
+    pub fn edit_fonts(&mut self, edit: impl FnOnce(&mut FontOptions)) {
+        let mut options = self.font_options().clone();
+        edit(&mut options);
+        self.set_font_options(options);
+    }
+
+    /// 设置字体角色的字号，本帧结束后生效
+    pub fn set_font_size(&mut self, role: FontRole, size: f32) {
+        self.edit_fonts(|options| options.spec_mut(role).size = size);
+    }
+
+    /// 按名称加入字体，本帧结束后通过[FrameContext::named_font]使用
+    pub fn add_font(&mut self, name: impl Into<String>, spec: FontSpec) {
+        let name = name.into();
+        self.edit_fonts(|options| {
+            options.extra.insert(name, spec);
+        });
+    }
+
+    /// 移除按名称加入的字体，本帧结束后生效
+    pub fn remove_font(&mut self, name: &str) {
+        self.edit_fonts(|options| {
+            options.extra.remove(name);
+        });
+    }
+
+    /// 热键状态，本帧已更新
+    pub fn hotkeys(&self) -> &Hotkeys {
+        self.hotkeys
+    }
+
+    /// 修改热键绑定，下一帧生效
+    pub fn hotkeys_mut(&mut self) -> &mut Hotkeys {
+        self.hotkeys
+    }
+
+    /// 当前交互模式
+    pub fn mode(&self) -> OverlayMode {
+        self.requested_mode.unwrap_or(self.mode)
+    }
+
+    /// 设置交互模式，下一帧生效
+    pub fn set_mode(&mut self, mode: OverlayMode) {
+        self.requested_mode = Some(mode);
+    }
+
+    /// 在交互模式与配置的模式之间切换，下一帧生效
+    pub fn toggle_mode(&mut self) {
+        self.requested_mode = Some(self.mode().toggled(self.base_mode));
+    }
+
+    /// 将当前imgui窗口标记为可交互，需在窗口的`build`中调用，仅对[OverlayMode::Regions]生效
+    pub fn mark_interactive(&mut self, ui: &Ui) {
+        self.hit_regions.mark_window(ui);
+    }
+
+    /// 标记本帧的可交互区域
+    pub fn add_interactive_region(&mut self, region: Region) {
+        self.hit_regions.push(region);
+    }
+
+    /// 可交互区域
+    pub fn hit_regions(&mut self) -> &mut HitRegions {
+        self.hit_regions
+    }
+}
+
+/// 样式的修改请求，只读访问时不产生修改
+pub struct StyleHandle<'a> {
+    current: &'a Style,
+    requested: &'a mut Option<Style>,
+}
+
+impl Deref for StyleHandle<'_> {
+    type Target = Style;
+
+    fn deref(&self) -> &Style {
+        self.requested.as_ref().unwrap_or(self.current)
+    }
+}
+
+impl DerefMut for StyleHandle<'_> {
+    fn deref_mut(&mut self) -> &mut Style {
+        self.requested.get_or_insert(*self.current)
+    }
+}
@@ -0,0 +1,457 @@
+use std::cell::Cell;
+use std::rc::Rc;
+use std::sync::atomic::{AtomicU32, Ordering};
+
+use imgui::Key;
+use windows::Win32::UI::Input::XboxController::{
+    XInputGetCapabilities, XInputGetState, XINPUT_CAPABILITIES, XINPUT_FLAG_GAMEPAD,
+    XINPUT_GAMEPAD_A, XINPUT_GAMEPAD_B, XINPUT_GAMEPAD_BACK, XINPUT_GAMEPAD_DPAD_DOWN,
+    XINPUT_GAMEPAD_DPAD_LEFT, XINPUT_GAMEPAD_DPAD_RIGHT, XINPUT_GAMEPAD_DPAD_UP,
+    XINPUT_GAMEPAD_LEFT_SHOULDER, XINPUT_GAMEPAD_LEFT_THUMB, XINPUT_GAMEPAD_RIGHT_SHOULDER,
+    XINPUT_GAMEPAD_RIGHT_THUMB, XINPUT_GAMEPAD_START, XINPUT_GAMEPAD_TRIGGER_THRESHOLD,
+    XINPUT_GAMEPAD_X, XINPUT_GAMEPAD_Y, XINPUT_STATE,
+};
+
+use crate::input::{InputEvent, InputPipeline};
+
+/// 收到`WM_DEVICECHANGE`的次数，XInput据此重新查找手柄
+static DEVICE_CHANGES: AtomicU32 = AtomicU32::new(0);
+
+/// 设备插拔后由窗口过程调用
+pub(crate) fn notify_device_change() {
+    DEVICE_CHANGES.fetch_add(1, Ordering::AcqRel);
+}
+
+/// 手柄按键，按位置命名(Xbox手柄的A为[GamepadButton::South])
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum GamepadButton {
+    South,
+    East,
+    West,
+    North,
+    DPadUp,
+    DPadDown,
+    DPadLeft,
+    DPadRight,
+    LeftBumper,
+    RightBumper,
+    LeftTrigger,
+    RightTrigger,
+    LeftStick,
+    RightStick,
+    Back,
+    Start,
+}
+
+impl GamepadButton {
+    pub const ALL: [GamepadButton; 16] = [
+        GamepadButton::South,
+        GamepadButton::East,
+        GamepadButton::West,
+        GamepadButton::North,
+        GamepadButton::DPadUp,
+        GamepadButton::DPadDown,
+        GamepadButton::DPadLeft,
+        GamepadButton::DPadRight,
+        GamepadButton::LeftBumper,
+        GamepadButton::RightBumper,
+        GamepadButton::LeftTrigger,
+        GamepadButton::RightTrigger,
+        GamepadButton::LeftStick,
+        GamepadButton::RightStick,
+        GamepadButton::Back,
+        GamepadButton::Start,
+    ];
+
+    /// 对应的imgui按键
+    pub fn imgui_key(self) -> Key {
+        match self {
+            GamepadButton::South => Key::GamepadFaceDown,
+            GamepadButton::East => Key::GamepadFaceRight,
+            GamepadButton::West => Key::GamepadFaceLeft,
+            GamepadButton::North => Key::GamepadFaceUp,
+            GamepadButton::DPadUp => Key::GamepadDpadUp,
+            GamepadButton::DPadDown => Key::GamepadDpadDown,
+            GamepadButton::DPadLeft => Key::GamepadDpadLeft,
+            GamepadButton::DPadRight => Key::GamepadDpadRight,
+            GamepadButton::LeftBumper => Key::GamepadL1,
+            GamepadButton::RightBumper => Key::GamepadR1,
+            GamepadButton::LeftTrigger => Key::GamepadL2,
+            GamepadButton::RightTrigger => Key::GamepadR2,
+            GamepadButton::LeftStick => Key::GamepadL3,
+            GamepadButton::RightStick => Key::GamepadR3,
+            GamepadButton::Back => Key::GamepadBack,
+            GamepadButton::Start => Key::GamepadStart,
+        }
+    }
+
+    fn bit(self) -> u16 {
+        1 << self as u16
+    }
+}
+
+/// 手柄某一时刻的状态
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct GamepadState {
+    buttons: u16,
+    /// 左摇杆，范围-1到1，向右、向上为正
+    pub left_stick: [f32; 2],
+    pub right_stick: [f32; 2],
+    /// 扳机，范围0到1，imgui使用此模拟量而非按下状态
+    pub left_trigger: f32,
+    pub right_trigger: f32,
+}
+
+impl GamepadState {
+    pub fn is_pressed(&self, button: GamepadButton) -> bool {
+        self.buttons & button.bit() != 0
+    }
+
+    pub fn set_pressed(&mut self, button: GamepadButton, pressed: bool) {
+        if pressed {
+            self.buttons |= button.bit();
+        } else {
+            self.buttons &= !button.bit();
+        }
+    }
+
+    /// 按下指定按键的状态，扳机同时设为完全按下
+    pub fn with_pressed(mut self, button: GamepadButton) -> GamepadState {
+        self.set_pressed(button, true);
+        match button {
+            GamepadButton::LeftTrigger => self.left_trigger = 1.0,
+            GamepadButton::RightTrigger => self.right_trigger = 1.0,
+            _ => {}
+        }
+        self
+    }
+
+    /// imgui按键及其模拟量，摇杆在死区内视为0
+    fn key_values(&self, dead_zone: f32) -> [(Key, f32); 24] {
+        let axis = |value: f32| {
+            if value.abs() <= dead_zone {
+                0.0
+            } else {
+                ((value.abs() - dead_zone) / (1.0 - dead_zone)).min(1.0)
+            }
+        };
+        let positive = |value: f32| if value > 0.0 { axis(value) } else { 0.0 };
+        let negative = |value: f32| if value < 0.0 { axis(value) } else { 0.0 };
+        let [lx, ly] = self.left_stick;
+        let [rx, ry] = self.right_stick;
+        let mut values = [(Key::GamepadStart, 0.0); 24];
+        for (index, button) in GamepadButton::ALL.into_iter().enumerate() {
+            // 扳机使用模拟量，按下状态只用于切换交互模式
+            let value = match button {
+                GamepadButton::LeftTrigger => self.left_trigger,
+                GamepadButton::RightTrigger => self.right_trigger,
+                _ => self.button_value(button),
+            };
+            values[index] = (button.imgui_key(), value);
+        }
+        let sticks = [
+            (Key::GamepadLStickLeft, negative(lx)),
+            (Key::GamepadLStickRight, positive(lx)),
+            (Key::GamepadLStickUp, positive(ly)),
+            (Key::GamepadLStickDown, negative(ly)),
+            (Key::GamepadRStickLeft, negative(rx)),
+            (Key::GamepadRStickRight, positive(rx)),
+            (Key::GamepadRStickUp, positive(ry)),
+            (Key::GamepadRStickDown, negative(ry)),
+        ];
+        values[GamepadButton::ALL.len()..].copy_from_slice(&sticks);
+        values
+    }
+
+    fn button_value(&self, button: GamepadButton) -> f32 {
+        if self.is_pressed(button) {
+            1.0
+        } else {
+            0.0
+        }
+    }
+}
+
+/// 手柄输入来源
+pub trait GamepadSource {
+    /// 读取当前状态，没有连接手柄时返回None
+    fn poll(&mut self) -> Option<GamepadState>;
+}
+
+/// 通过XInput读取第一个已连接的Xbox兼容手柄
+#[derive(Default)]
+pub struct XInputSource {
+    /// 找到的手柄序号
+    user_index: Option<u32>,
+    /// 上次查找手柄时的设备变化次数，None表示尚未查找
+    probed: Option<u32>,
+}
+
+impl XInputSource {
+    /// 对未连接的序号调用`XInputGetState`很慢，只在设备变化后查找，优先使用上次的手柄
+    fn probe(&mut self) {
+        let changes = DEVICE_CHANGES.load(Ordering::Acquire);
+        if self.probed == Some(changes) {
+            return;
+        }
+        self.probed = Some(changes);
+        let first = self.user_index.unwrap_or(0);
+        self.user_index = (0..4).map(|offset| (first + offset) % 4).find(|index| {
+            let mut capabilities = XINPUT_CAPABILITIES::default();
+            unsafe { XInputGetCapabilities(*index, XINPUT_FLAG_GAMEPAD, &mut capabilities) == 0 }
+        });
+    }
+}
+
+impl GamepadSource for XInputSource {
+    fn poll(&mut self) -> Option<GamepadState> {
+        self.probe();
+        let index = self.user_index?;
+        let mut state = XINPUT_STATE::default();
+        if unsafe { XInputGetState(index, &mut state) } != 0 {
+            // 断开后等待下一次设备变化再查找
+            self.user_index = None;
+            return None;
+        }
+        let pad = state.Gamepad;
+        let mut result = GamepadState {
+            left_stick: [stick(pad.sThumbLX), stick(pad.sThumbLY)],
+            right_stick: [stick(pad.sThumbRX), stick(pad.sThumbRY)],
+            left_trigger: trigger(pad.bLeftTrigger),
+            right_trigger: trigger(pad.bRightTrigger),
+            ..GamepadState::default()
+        };
+        result.set_pressed(GamepadButton::LeftTrigger, result.left_trigger > 0.0);
+        result.set_pressed(GamepadButton::RightTrigger, result.right_trigger > 0.0);
+        for (flag, button) in [
+            (XINPUT_GAMEPAD_A, GamepadButton::South),
+            (XINPUT_GAMEPAD_B, GamepadButton::East),
+            (XINPUT_GAMEPAD_X, GamepadButton::West),
+            (XINPUT_GAMEPAD_Y, GamepadButton::North),
+            (XINPUT_GAMEPAD_DPAD_UP, GamepadButton::DPadUp),
+            (XINPUT_GAMEPAD_DPAD_DOWN, GamepadButton::DPadDown),
+            (XINPUT_GAMEPAD_DPAD_LEFT, GamepadButton::DPadLeft),
+            (XINPUT_GAMEPAD_DPAD_RIGHT, GamepadButton::DPadRight),
+            (XINPUT_GAMEPAD_LEFT_SHOULDER, GamepadButton::LeftBumper),
+            (XINPUT_GAMEPAD_RIGHT_SHOULDER, GamepadButton::RightBumper),
+            (XINPUT_GAMEPAD_LEFT_THUMB, GamepadButton::LeftStick),
+            (XINPUT_GAMEPAD_RIGHT_THUMB, GamepadButton::RightStick),
+            (XINPUT_GAMEPAD_BACK, GamepadButton::Back),
+            (XINPUT_GAMEPAD_START, GamepadButton::Start),
+        ] {
+            result.set_pressed(button, pad.wButtons.0 & flag.0 != 0);
+        }
+        Some(result)
+    }
+}
+
+fn stick(value: i16) -> f32 {
+    (value as f32 / 32767.0).max(-1.0)
+}
+
+/// 低于XInput建议阈值的扳机值视为未按下
+fn trigger(value: u8) -> f32 {
+    if value < XINPUT_GAMEPAD_TRIGGER_THRESHOLD.0 as u8 {
+        0.0
+    } else {
+        value as f32 / 255.0
+    }
+}
+
+/// 通过gilrs读取手柄，支持XInput以外的手柄(如DualShock)
+#[cfg(feature = "gamepad")]
+pub struct GilrsSource {
+    gilrs: gilrs::Gilrs,
+}
+
+#[cfg(feature = "gamepad")]
+impl GilrsSource {
+    pub fn new() -> Result<GilrsSource, gilrs::Error> {
+        Ok(GilrsSource {
+            gilrs: gilrs::Gilrs::new()?,
+        })
+    }
+}
+
+#[cfg(feature = "gamepad")]
+impl GamepadSource for GilrsSource {
+    fn poll(&mut self) -> Option<GamepadState> {
+        use gilrs::{Axis, Button};
+        // 处理事件队列以更新手柄状态
+        while self.gilrs.next_event().is_some() {}
+        let (_, gamepad) = self
+            .gilrs
+            .gamepads()
+            .find(|(_, gamepad)| gamepad.is_connected())?;
+        let trigger = |button: Button| {
+            gamepad
+                .button_data(button)
+                .map(|data| data.value())
+                .unwrap_or_default()
+        };
+        let mut result = GamepadState {
+            left_stick: [
+                gamepad.value(Axis::LeftStickX),
+                gamepad.value(Axis::LeftStickY),
+            ],
+            right_stick: [
+                gamepad.value(Axis::RightStickX),
+                gamepad.value(Axis::RightStickY),
+            ],
+            left_trigger: trigger(Button::LeftTrigger2),
+            right_trigger: trigger(Button::RightTrigger2),
+            ..GamepadState::default()
+        };
+        for (button, target) in [
+            (Button::South, GamepadButton::South),
+            (Button::East, GamepadButton::East),
+            (Button::West, GamepadButton::West),
+            (Button::North, GamepadButton::North),
+            (Button::DPadUp, GamepadButton::DPadUp),
+            (Button::DPadDown, GamepadButton::DPadDown),
+            (Button::DPadLeft, GamepadButton::DPadLeft),
+            (Button::DPadRight, GamepadButton::DPadRight),
+            (Button::LeftTrigger, GamepadButton::LeftBumper),
+            (Button::RightTrigger, GamepadButton::RightBumper),
+            (Button::LeftTrigger2, GamepadButton::LeftTrigger),
+            (Button::RightTrigger2, GamepadButton::RightTrigger),
+            (Button::LeftThumb, GamepadButton::LeftStick),
+            (Button::RightThumb, GamepadButton::RightStick),
+            (Button::Select, GamepadButton::Back),
+            (Button::Start, GamepadButton::Start),
+        ] {
+            result.set_pressed(target, gamepad.is_pressed(button));
+        }
+        Some(result)
+    }
+}
+
+/// 由代码控制的手柄，克隆后共享同一状态，用于测试与脚本输入
+#[derive(Clone, Debug, Default)]
+pub struct MockGamepad {
+    state: Rc<Cell<Option<GamepadState>>>,
+}
+
+impl MockGamepad {
+    pub fn new() -> MockGamepad {
+        MockGamepad::default()
+    }
+
+    /// 设置状态，同时视为已连接
+    pub fn set(&self, state: GamepadState) {
+        self.state.set(Some(state));
+    }
+
+    pub fn disconnect(&self) {
+        self.state.set(None);
+    }
+}
+
+impl GamepadSource for MockGamepad {
+    fn poll(&mut self) -> Option<GamepadState> {
+        self.state.get()
+    }
+}
+
+/// 手柄选项
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GamepadOptions {
+    /// 是否读取手柄，手柄只在交互模式下用于imgui导航
+    pub enabled: bool,
+    /// 切换交互模式的按键
+    pub toggle: Option<GamepadButton>,
+    /// 摇杆死区，范围0到1
+    pub dead_zone: f32,
+}
+
+impl Default for GamepadOptions {
+    fn default() -> GamepadOptions {
+        GamepadOptions {
+            enabled: true,
+            toggle: None,
+            dead_zone: 0.25,
+        }
+    }
+}
+
+#[cfg(feature = "gamepad")]
+fn default_source() -> Box<dyn GamepadSource> {
+    match GilrsSource::new() {
+        Ok(source) => Box::new(source),
+        Err(e) => {
+            log::warn!("gilrs unavailable, falling back to XInput: {e}");
+            Box::new(XInputSource::default())
+        }
+    }
+}
+
+#[cfg(not(feature = "gamepad"))]
+fn default_source() -> Box<dyn GamepadSource> {
+    Box::new(XInputSource::default())
+}
+
+/// 将手柄状态转换为imgui按键事件
+pub(crate) struct Gamepad {
+    source: Option<Box<dyn GamepadSource>>,
+    options: GamepadOptions,
+    previous: GamepadState,
+    /// 已转发给imgui的状态
+    forwarded: GamepadState,
+    connected: bool,
+}
+
+impl Gamepad {
+    /// 启用时优先使用gilrs，不可用时使用XInput
+    pub(crate) fn new(options: GamepadOptions) -> Gamepad {
+        Gamepad {
+            source: options.enabled.then(default_source),
+            options,
+            previous: GamepadState::default(),
+            forwarded: GamepadState::default(),
+            connected: false,
+        }
+    }
+
+    pub(crate) fn set_source(&mut self, source: Box<dyn GamepadSource>) {
+        self.source = Some(source);
+    }
+
+    pub(crate) fn is_connected(&self) -> bool {
+        self.connected
+    }
+
+    /// 读取手柄，[forward]为true时将变化的按键加入输入管线，为false时松开已转发的按键。
+    /// 返回是否按下了切换交互模式的按键
+    pub(crate) fn update(&mut self, input: &mut InputPipeline, forward: bool) -> bool {
+        let Some(source) = &mut self.source else {
+            return false;
+        };
+        let state = source.poll();
+        self.connected = state.is_some();
+        // 断开时松开所有按键
+        let state = state.unwrap_or_default();
+        let forwarded = if forward {
+            state
+        } else {
+            GamepadState::default()
+        };
+        let dead_zone = self.options.dead_zone;
+        let previous = self.forwarded.key_values(dead_zone);
+        for (index, (key, value)) in forwarded.key_values(dead_zone).into_iter().enumerate() {
+            if value != previous[index].1 {
+                input.push(InputEvent::KeyAnalog {
+                    key,
+                    down: value > 0.0,
+                    value,
+                });
+            }
+        }
+        let toggled = match self.options.toggle {
+            Some(button) => state.is_pressed(button) && !self.previous.is_pressed(button),
+            None => false,
+        };
+        self.previous = state;
+        self.forwarded = forwarded;
+        toggled
+    }
+}
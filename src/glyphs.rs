@@ -0,0 +1,164 @@
+use std::collections::BTreeSet;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use imgui::FontGlyphRanges;
+
+/// 按需加载的字符集，用于[crate::GlyphRanges::Dynamic]。
+/// 绘制前通过[crate::FrameContext::request_glyphs]登记文本，缺失的字符在本帧结束后加入字体图集
+#[derive(Debug)]
+pub struct DynamicGlyphs {
+    loaded: BTreeSet<char>,
+    pending: BTreeSet<char>,
+    /// 成对的起止码点，以0结尾，字体图集重建前须保持不变
+    ranges: Vec<u32>,
+    cache: Option<PathBuf>,
+}
+
+impl Default for DynamicGlyphs {
+    fn default() -> DynamicGlyphs {
+        DynamicGlyphs::with_loaded(BTreeSet::new(), None)
+    }
+}
+
+impl DynamicGlyphs {
+    pub fn new() -> DynamicGlyphs {
+        DynamicGlyphs::default()
+    }
+
+    /// 使用缓存文件，启动时加入上次运行用到的字符，新字符加入图集时写回
+    pub fn with_cache(path: impl Into<PathBuf>) -> DynamicGlyphs {
+        let path = path.into();
+        let loaded = match fs::read_to_string(&path) {
+            Ok(text) => text.chars().filter(|c| !c.is_control()).collect(),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => BTreeSet::new(),
+            Err(e) => {
+                log::warn!("Failed to read glyph cache {}: {e}", path.display());
+                BTreeSet::new()
+            }
+        };
+        DynamicGlyphs::with_loaded(loaded, Some(path))
+    }
+
+    fn with_loaded(loaded: BTreeSet<char>, cache: Option<PathBuf>) -> DynamicGlyphs {
+        let mut glyphs = DynamicGlyphs {
+            loaded,
+            pending: BTreeSet::new(),
+            ranges: Vec::new(),
+            cache,
+        };
+        glyphs.update_ranges();
+        glyphs
+    }
+
+    pub fn cache(&self) -> Option<&Path> {
+        self.cache.as_deref()
+    }
+
+    /// 已加入图集的字符数
+    pub fn len(&self) -> usize {
+        self.loaded.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.loaded.is_empty()
+    }
+
+    pub fn contains(&self, c: char) -> bool {
+        c.is_ascii() || self.loaded.contains(&c)
+    }
+
+    /// 登记文本中的字符，尚未加入图集的字符留待下次重建
+    pub fn request(&mut self, text: &str) {
+        for c in text.chars() {
+            if !c.is_control() && !self.contains(c) {
+                self.pending.insert(c);
+            }
+        }
+    }
+
+    /// 是否有等待加入图集的字符
+    pub fn has_pending(&self) -> bool {
+        !self.pending.is_empty()
+    }
+
+    /// 将等待的字符加入字符集并写回缓存，之后须重建字体图集
+    pub(crate) fn commit(&mut self) {
+        if self.pending.is_empty() {
+            return;
+        }
+        log::debug!("Loading {} glyphs on demand", self.pending.len());
+        self.loaded.append(&mut self.pending);
+        self.update_ranges();
+        if let Some(path) = &self.cache {
+            let text: String = self.loaded.iter().collect();
+            if let Err(e) = fs::write(path, text) {
+                log::warn!("Failed to write glyph cache {}: {e}", path.display());
+            }
+        }
+    }
+
+    /// 基本拉丁字母加上字符集，相邻码点合并为一个范围
+    fn update_ranges(&mut self) {
+        let mut ranges = vec![0x0020, 0x007E];
+        for c in self.loaded.iter().map(|c| *c as u32) {
+            let last = ranges.len() - 1;
+            if c <= ranges[last] {
+                continue;
+            }
+            if c == ranges[last] + 1 {
+                ranges[last] = c;
+            } else {
+                ranges.extend([c, c]);
+            }
+        }
+        ranges.push(0);
+        self.ranges = ranges;
+    }
+
+    pub(crate) fn imgui_ranges(&self) -> FontGlyphRanges {
+        // 范围只在commit时替换，替换后会清空并重建字体图集，不会留下悬垂指针
+        unsafe { FontGlyphRanges::from_ptr(self.ranges.as_ptr()) }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn adjacent_glyphs_merge_into_one_range() {
+        let mut glyphs = DynamicGlyphs::new();
+        glyphs.request("七一丁");
+        assert!(glyphs.has_pending());
+        glyphs.commit();
+        assert!(!glyphs.has_pending());
+        // 一(4E00)与丁(4E01)相邻，七(4E03)单独一段
+        assert_eq!(
+            glyphs.ranges,
+            [0x20, 0x7E, 0x4E00, 0x4E01, 0x4E03, 0x4E03, 0]
+        );
+    }
+
+    #[test]
+    fn control_characters_are_skipped() {
+        let mut glyphs = DynamicGlyphs::new();
+        glyphs.request("\u{7F}\u{80}¡¢£");
+        glyphs.commit();
+        assert_eq!(glyphs.ranges, [0x20, 0x7E, 0xA1, 0xA3, 0]);
+    }
+
+    #[test]
+    fn ascii_and_loaded_glyphs_are_not_requested() {
+        let mut glyphs = DynamicGlyphs::new();
+        glyphs.request("hello\n");
+        assert!(!glyphs.has_pending());
+        glyphs.request("你好");
+        glyphs.commit();
+        assert_eq!(glyphs.len(), 2);
+        glyphs.request("你好");
+        assert!(!glyphs.has_pending());
+        assert!(glyphs.contains('好') && !glyphs.contains('们'));
+    }
+}
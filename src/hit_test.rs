@@ -0,0 +1,112 @@
+use std::collections::HashMap;
+
+use imgui::Ui;
+
+/// 覆盖窗口坐标系下的矩形区域
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Region {
+    pub min: [f32; 2],
+    pub max: [f32; 2],
+}
+
+impl Region {
+    pub fn new(pos: [f32; 2], size: [f32; 2]) -> Region {
+        Region {
+            min: pos,
+            max: [pos[0] + size[0], pos[1] + size[1]],
+        }
+    }
+
+    pub fn contains(&self, point: [f32; 2]) -> bool {
+        point[0] >= self.min[0]
+            && point[0] < self.max[0]
+            && point[1] >= self.min[1]
+            && point[1] < self.max[1]
+    }
+}
+
+/// 可交互区域，区域外的鼠标输入穿透到目标窗口。
+/// 每帧开始时按光标位置切换整个窗口的穿透，进出区域后最多晚一帧生效
+#[derive(Clone, Debug, Default)]
+pub struct HitRegions {
+    /// 用户注册的区域，一直有效直到移除
+    persistent: HashMap<String, Region>,
+    /// 本帧标记的区域
+    current: Vec<Region>,
+    /// 上一帧标记的区域，在本帧开始前用于命中测试
+    previous: Vec<Region>,
+}
+
+impl HitRegions {
+    /// 注册区域，同名区域会被覆盖
+    pub fn insert(&mut self, name: &str, region: Region) {
+        self.persistent.insert(name.to_string(), region);
+    }
+
+    /// 移除注册的区域
+    pub fn remove(&mut self, name: &str) -> Option<Region> {
+        self.persistent.remove(name)
+    }
+
+    /// 标记本帧的可交互区域
+    pub fn push(&mut self, region: Region) {
+        self.current.push(region);
+    }
+
+    /// 将当前imgui窗口标记为可交互，需在窗口的`build`中调用
+    pub fn mark_window(&mut self, ui: &Ui) {
+        self.push(Region::new(ui.window_pos(), ui.window_size()));
+    }
+
+    /// 点是否落在可交互区域内
+    pub fn contains(&self, point: [f32; 2]) -> bool {
+        self.persistent
+            .values()
+            .any(|region| region.contains(point))
+            || self.previous.iter().any(|region| region.contains(point))
+    }
+
+    /// 帧结束，本帧标记的区域用于下一帧的命中测试
+    pub(crate) fn end_frame(&mut self) {
+        std::mem::swap(&mut self.previous, &mut self.current);
+        self.current.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn region_excludes_its_max_edge() {
+        let region = Region::new([10.0, 20.0], [30.0, 40.0]);
+        assert!(region.contains([10.0, 20.0]));
+        assert!(region.contains([39.9, 59.9]));
+        assert!(!region.contains([40.0, 30.0]));
+        assert!(!region.contains([20.0, 60.0]));
+    }
+
+    #[test]
+    fn marked_regions_apply_after_end_frame() {
+        let mut regions = HitRegions::default();
+        regions.push(Region::new([0.0, 0.0], [10.0, 10.0]));
+        assert!(!regions.contains([5.0, 5.0]));
+        regions.end_frame();
+        assert!(regions.contains([5.0, 5.0]));
+        // 下一帧没有再标记，区域失效
+        regions.end_frame();
+        assert!(!regions.contains([5.0, 5.0]));
+    }
+
+    #[test]
+    fn persistent_regions_survive_frames() {
+        let mut regions = HitRegions::default();
+        regions.insert("menu", Region::new([0.0, 0.0], [10.0, 10.0]));
+        assert!(regions.contains([5.0, 5.0]));
+        regions.end_frame();
+        regions.end_frame();
+        assert!(regions.contains([5.0, 5.0]));
+        assert!(regions.remove("menu").is_some());
+        assert!(!regions.contains([5.0, 5.0]));
+    }
+}
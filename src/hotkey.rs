@@ -0,0 +1,437 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+
+use windows::Win32::UI::Input::KeyboardAndMouse::GetAsyncKeyState;
+
+macro_rules! keys {
+    ($($key:ident => $name:literal, $vk:literal;)*) => {
+        /// 按键，与平台无关，按名称序列化
+        #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+        pub enum Key {
+            $($key,)*
+        }
+
+        impl Key {
+            /// 所有按键
+            pub const ALL: &'static [Key] = &[$(Key::$key,)*];
+
+            /// 按键名称，与[Key::from_name]互逆
+            pub fn name(self) -> &'static str {
+                match self {
+                    $(Key::$key => $name,)*
+                }
+            }
+
+            /// Windows虚拟键码
+            pub fn vk(self) -> i32 {
+                match self {
+                    $(Key::$key => $vk,)*
+                }
+            }
+        }
+    };
+}
+
+keys! {
+    A => "A", 0x41; B => "B", 0x42; C => "C", 0x43; D => "D", 0x44; E => "E", 0x45;
+    F => "F", 0x46; G => "G", 0x47; H => "H", 0x48; I => "I", 0x49; J => "J", 0x4A;
+    K => "K", 0x4B; L => "L", 0x4C; M => "M", 0x4D; N => "N", 0x4E; O => "O", 0x4F;
+    P => "P", 0x50; Q => "Q", 0x51; R => "R", 0x52; S => "S", 0x53; T => "T", 0x54;
+    U => "U", 0x55; V => "V", 0x56; W => "W", 0x57; X => "X", 0x58; Y => "Y", 0x59;
+    Z => "Z", 0x5A;
+    Num0 => "0", 0x30; Num1 => "1", 0x31; Num2 => "2", 0x32; Num3 => "3", 0x33;
+    Num4 => "4", 0x34; Num5 => "5", 0x35; Num6 => "6", 0x36; Num7 => "7", 0x37;
+    Num8 => "8", 0x38; Num9 => "9", 0x39;
+    F1 => "F1", 0x70; F2 => "F2", 0x71; F3 => "F3", 0x72; F4 => "F4", 0x73;
+    F5 => "F5", 0x74; F6 => "F6", 0x75; F7 => "F7", 0x76; F8 => "F8", 0x77;
+    F9 => "F9", 0x78; F10 => "F10", 0x79; F11 => "F11", 0x7A; F12 => "F12", 0x7B;
+    Escape => "Escape", 0x1B; Enter => "Enter", 0x0D; Space => "Space", 0x20;
+    Tab => "Tab", 0x09; Backspace => "Backspace", 0x08;
+    Insert => "Insert", 0x2D; Delete => "Delete", 0x2E; Home => "Home", 0x24;
+    End => "End", 0x23; PageUp => "PageUp", 0x21; PageDown => "PageDown", 0x22;
+    Left => "Left", 0x25; Up => "Up", 0x26; Right => "Right", 0x27; Down => "Down", 0x28;
+    CapsLock => "CapsLock", 0x14; ScrollLock => "ScrollLock", 0x91;
+    NumLock => "NumLock", 0x90; PrintScreen => "PrintScreen", 0x2C; Pause => "Pause", 0x13;
+    Numpad0 => "Numpad0", 0x60; Numpad1 => "Numpad1", 0x61; Numpad2 => "Numpad2", 0x62;
+    Numpad3 => "Numpad3", 0x63; Numpad4 => "Numpad4", 0x64; Numpad5 => "Numpad5", 0x65;
+    Numpad6 => "Numpad6", 0x66; Numpad7 => "Numpad7", 0x67; Numpad8 => "Numpad8", 0x68;
+    Numpad9 => "Numpad9", 0x69; NumpadMultiply => "NumpadMultiply", 0x6A;
+    NumpadAdd => "NumpadAdd", 0x6B; NumpadSubtract => "NumpadSubtract", 0x6D;
+    NumpadDecimal => "NumpadDecimal", 0x6E; NumpadDivide => "NumpadDivide", 0x6F;
+    Minus => "Minus", 0xBD; Equal => "Equal", 0xBB; Comma => "Comma", 0xBC;
+    Period => "Period", 0xBE; Semicolon => "Semicolon", 0xBA; Slash => "Slash", 0xBF;
+    Backslash => "Backslash", 0xDC; Apostrophe => "Apostrophe", 0xDE;
+    GraveAccent => "GraveAccent", 0xC0; LeftBracket => "LeftBracket", 0xDB;
+    RightBracket => "RightBracket", 0xDD;
+    Ctrl => "Ctrl", 0x11; Shift => "Shift", 0x10; Alt => "Alt", 0x12; Super => "Super", 0x5B;
+    Menu => "Menu", 0x5D;
+    MouseLeft => "MouseLeft", 0x01; MouseRight => "MouseRight", 0x02;
+    MouseMiddle => "MouseMiddle", 0x04; MouseX1 => "MouseX1", 0x05; MouseX2 => "MouseX2", 0x06;
+}
+
+impl Key {
+    /// 按名称查找，不区分大小写，支持`Esc`、`Control`、`Win`等常用别名
+    pub fn from_name(name: &str) -> Option<Key> {
+        let name = name.trim();
+        let alias = match name.to_ascii_lowercase().as_str() {
+            "control" => Some(Key::Ctrl),
+            "win" | "cmd" | "meta" => Some(Key::Super),
+            "esc" => Some(Key::Escape),
+            "return" => Some(Key::Enter),
+            "ins" => Some(Key::Insert),
+            "del" => Some(Key::Delete),
+            "pgup" => Some(Key::PageUp),
+            "pgdn" => Some(Key::PageDown),
+            "-" => Some(Key::Minus),
+            "=" => Some(Key::Equal),
+            "," => Some(Key::Comma),
+            "." => Some(Key::Period),
+            "`" => Some(Key::GraveAccent),
+            _ => None,
+        };
+        alias.or_else(|| {
+            Key::ALL
+                .iter()
+                .copied()
+                .find(|key| key.name().eq_ignore_ascii_case(name))
+        })
+    }
+
+    /// 转换为修饰键
+    fn modifier(self) -> Option<Modifiers> {
+        match self {
+            Key::Ctrl => Some(Modifiers::CTRL),
+            Key::Shift => Some(Modifiers::SHIFT),
+            Key::Alt => Some(Modifiers::ALT),
+            Key::Super => Some(Modifiers::SUPER),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Key {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// 修饰键组合
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Modifiers(u8);
+
+impl Modifiers {
+    pub const NONE: Modifiers = Modifiers(0);
+    pub const CTRL: Modifiers = Modifiers(1);
+    pub const SHIFT: Modifiers = Modifiers(1 << 1);
+    pub const ALT: Modifiers = Modifiers(1 << 2);
+    pub const SUPER: Modifiers = Modifiers(1 << 3);
+
+    pub const fn contains(self, other: Modifiers) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    /// 当前按下的修饰键
+    pub fn current(source: &dyn KeySource) -> Modifiers {
+        [Key::Ctrl, Key::Shift, Key::Alt, Key::Super]
+            .into_iter()
+            .filter(|key| source.is_down(*key))
+            .filter_map(Key::modifier)
+            .fold(Modifiers::NONE, |all, modifier| all | modifier)
+    }
+}
+
+impl std::ops::BitOr for Modifiers {
+    type Output = Modifiers;
+
+    fn bitor(self, rhs: Modifiers) -> Modifiers {
+        Modifiers(self.0 | rhs.0)
+    }
+}
+
+/// 组合键，修饰键需完全一致，其余按键需同时按下，例如`Ctrl+Shift+F1`
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Chord {
+    pub modifiers: Modifiers,
+    pub keys: Vec<Key>,
+}
+
+impl Chord {
+    pub fn new(modifiers: Modifiers, keys: &[Key]) -> Chord {
+        Chord {
+            modifiers,
+            keys: keys.to_vec(),
+        }
+    }
+
+    /// 组合键是否处于按下状态
+    pub fn is_down(&self, source: &dyn KeySource) -> bool {
+        !self.keys.is_empty()
+            && Modifiers::current(source) == self.modifiers
+            && self.keys.iter().all(|key| source.is_down(*key))
+    }
+}
+
+impl From<Key> for Chord {
+    fn from(key: Key) -> Chord {
+        match key.modifier() {
+            // 单独的修饰键本身就是要按下的键
+            Some(modifier) => Chord::new(modifier, &[key]),
+            None => Chord::new(Modifiers::NONE, &[key]),
+        }
+    }
+}
+
+impl fmt::Display for Chord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut parts: Vec<&str> = Vec::new();
+        for (modifier, key) in [
+            (Modifiers::CTRL, Key::Ctrl),
+            (Modifiers::SHIFT, Key::Shift),
+            (Modifiers::ALT, Key::Alt),
+            (Modifiers::SUPER, Key::Super),
+        ] {
+            if self.modifiers.contains(modifier) && !self.keys.contains(&key) {
+                parts.push(key.name());
+            }
+        }
+        parts.extend(self.keys.iter().map(|key| key.name()));
+        f.write_str(&parts.join("+"))
+    }
+}
+
+/// 组合键解析失败
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseChordError(pub String);
+
+impl fmt::Display for ParseChordError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid hotkey: {}", self.0)
+    }
+}
+
+impl std::error::Error for ParseChordError {}
+
+impl FromStr for Chord {
+    type Err = ParseChordError;
+
+    fn from_str(s: &str) -> Result<Chord, ParseChordError> {
+        let parts: Vec<&str> = s.split('+').map(str::trim).collect();
+        let mut modifiers = Modifiers::NONE;
+        let mut keys = Vec::new();
+        for (index, part) in parts.iter().enumerate() {
+            let key = Key::from_name(part).ok_or_else(|| ParseChordError(s.to_string()))?;
+            let last = index == parts.len() - 1;
+            match key.modifier() {
+                Some(modifier) if !last => modifiers = modifiers | modifier,
+                Some(modifier) => {
+                    modifiers = modifiers | modifier;
+                    keys.push(key);
+                }
+                None => keys.push(key),
+            }
+        }
+        if keys.is_empty() {
+            return Err(ParseChordError(s.to_string()));
+        }
+        Ok(Chord { modifiers, keys })
+    }
+}
+
+/// 按键状态来源
+pub trait KeySource {
+    fn is_down(&self, key: Key) -> bool;
+}
+
+/// 通过`GetAsyncKeyState`读取按键状态
+pub struct Win32KeySource;
+
+impl KeySource for Win32KeySource {
+    fn is_down(&self, key: Key) -> bool {
+        let down = |vk: i32| unsafe { GetAsyncKeyState(vk) } as u16 & 0x8000 != 0;
+        match key {
+            // 左右Win键
+            Key::Super => down(0x5B) || down(0x5C),
+            _ => down(key.vk()),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+struct HotkeyState {
+    down: bool,
+    pressed: bool,
+    released: bool,
+    double_tapped: bool,
+    down_since: Option<Instant>,
+    last_press: Option<Instant>,
+}
+
+/// 热键注册表，每帧更新一次，按名称查询
+#[derive(Clone, Debug)]
+pub struct Hotkeys {
+    bindings: HashMap<String, Chord>,
+    states: HashMap<String, HotkeyState>,
+    /// 两次按下的间隔小于该值视为双击
+    pub double_tap_interval: Duration,
+}
+
+impl Default for Hotkeys {
+    fn default() -> Self {
+        Hotkeys {
+            bindings: HashMap::new(),
+            states: HashMap::new(),
+            double_tap_interval: Duration::from_millis(300),
+        }
+    }
+}
+
+impl Hotkeys {
+    pub fn new() -> Hotkeys {
+        Hotkeys::default()
+    }
+
+    /// 绑定动作，已存在时覆盖
+    pub fn bind(&mut self, action: &str, chord: impl Into<Chord>) -> &mut Hotkeys {
+        self.bindings.insert(action.to_string(), chord.into());
+        self.states
+            .insert(action.to_string(), HotkeyState::default());
+        self
+    }
+
+    /// 通过字符串绑定动作，例如`Ctrl+Shift+F1`
+    pub fn bind_str(&mut self, action: &str, chord: &str) -> Result<&mut Hotkeys, ParseChordError> {
+        Ok(self.bind(action, chord.parse::<Chord>()?))
+    }
+
+    /// 解除绑定
+    pub fn unbind(&mut self, action: &str) -> Option<Chord> {
+        self.states.remove(action);
+        self.bindings.remove(action)
+    }
+
+    /// 动作绑定的组合键
+    pub fn binding(&self, action: &str) -> Option<&Chord> {
+        self.bindings.get(action)
+    }
+
+    /// 所有绑定
+    pub fn bindings(&self) -> impl Iterator<Item = (&str, &Chord)> {
+        self.bindings
+            .iter()
+            .map(|(action, chord)| (action.as_str(), chord))
+    }
+
+    /// 本帧刚按下
+    pub fn pressed(&self, action: &str) -> bool {
+        self.state(action, |state| state.pressed)
+    }
+
+    /// 本帧刚松开
+    pub fn released(&self, action: &str) -> bool {
+        self.state(action, |state| state.released)
+    }
+
+    /// 处于按下状态
+    pub fn held(&self, action: &str) -> bool {
+        self.state(action, |state| state.down)
+    }
+
+    /// 已按住的时长
+    pub fn held_for(&self, action: &str) -> Option<Duration> {
+        self.states
+            .get(action)
+            .and_then(|state| state.down_since)
+            .map(|since| since.elapsed())
+    }
+
+    /// 本帧完成双击
+    pub fn double_tapped(&self, action: &str) -> bool {
+        self.state(action, |state| state.double_tapped)
+    }
+
+    fn state(&self, action: &str, f: impl Fn(&HotkeyState) -> bool) -> bool {
+        self.states.get(action).map(f).unwrap_or(false)
+    }
+
+    /// 更新所有热键状态，每帧调用一次
+    pub fn update(&mut self, source: &dyn KeySource) {
+        self.update_at(source, Instant::now())
+    }
+
+    /// 以指定时间更新，便于回放或测试
+    pub fn update_at(&mut self, source: &dyn KeySource, now: Instant) {
+        for (action, chord) in &self.bindings {
+            let state = self.states.entry(action.clone()).or_default();
+            let down = chord.is_down(source);
+            state.pressed = down && !state.down;
+            state.released = !down && state.down;
+            state.double_tapped = false;
+            if state.pressed {
+                state.down_since = Some(now);
+                if let Some(last) = state.last_press {
+                    if now.duration_since(last) <= self.double_tap_interval {
+                        state.double_tapped = true;
+                    }
+                }
+                // 双击后重新计数，避免连续三次按下触发两次
+                state.last_press = if state.double_tapped { None } else { Some(now) };
+            }
+            if !down {
+                state.down_since = None;
+            }
+            state.down = down;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Pressed(&'static [Key]);
+
+    impl KeySource for Pressed {
+        fn is_down(&self, key: Key) -> bool {
+            self.0.contains(&key)
+        }
+    }
+
+    #[test]
+    fn chord_round_trips() {
+        for text in [
+            "Ctrl+Shift+F1",
+            "Alt+Enter",
+            "Shift",
+            "Ctrl+Alt",
+            "Super+A+B",
+        ] {
+            let chord: Chord = text.parse().unwrap();
+            assert_eq!(chord.to_string(), text);
+            assert_eq!(chord.to_string().parse::<Chord>().unwrap(), chord);
+        }
+    }
+
+    #[test]
+    fn chord_accepts_aliases() {
+        let chord: Chord = " control + esc ".parse().unwrap();
+        assert_eq!(chord, Chord::new(Modifiers::CTRL, &[Key::Escape]));
+        assert_eq!(chord.to_string(), "Ctrl+Escape");
+    }
+
+    #[test]
+    fn chord_rejects_unknown_keys() {
+        for text in ["", "Ctrl+", "Ctrl+Nope", "F1++"] {
+            assert!(text.parse::<Chord>().is_err(), "{text:?}");
+        }
+    }
+
+    #[test]
+    fn chord_requires_exact_modifiers() {
+        let chord: Chord = "Ctrl+A".parse().unwrap();
+        assert!(chord.is_down(&Pressed(&[Key::Ctrl, Key::A])));
+        assert!(!chord.is_down(&Pressed(&[Key::A])));
+        assert!(!chord.is_down(&Pressed(&[Key::Ctrl, Key::Shift, Key::A])));
+    }
+}
@@ -0,0 +1,192 @@
+use std::cell::Cell;
+use std::fmt;
+use std::path::PathBuf;
+
+use imgui::Ui;
+
+use crate::fonts::GlyphRanges;
+
+/// 私用区，两套图标字体的字形都在此范围内
+static ICON_RANGES: [u32; 3] = [0xE000, 0xF8FF, 0];
+
+/// 图标字体
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum IconSet {
+    /// Font Awesome 6 Free Solid
+    #[default]
+    FontAwesome,
+    /// Material Symbols或Material Icons
+    MaterialSymbols,
+}
+
+/// 合并到各字体角色中的图标字体
+#[derive(Clone, Debug, PartialEq)]
+pub struct IconFont {
+    pub set: IconSet,
+    /// 字体文件，不存在时作为字体族名查找，例如`Font Awesome 6 Free Solid`
+    pub path: PathBuf,
+    pub ranges: GlyphRanges,
+    /// 图标字号与所在字体字号之比
+    pub size_ratio: f32,
+    /// 字形偏移，相对图标字号，用于对齐基线
+    pub glyph_offset: [f32; 2],
+    /// 等宽图标，便于在列表中对齐
+    pub monospace: bool,
+}
+
+impl IconFont {
+    /// Font Awesome的字形占满字身，缩小到2/3以与文字等高
+    pub fn font_awesome(path: impl Into<PathBuf>) -> IconFont {
+        IconFont {
+            set: IconSet::FontAwesome,
+            path: path.into(),
+            ranges: GlyphRanges::Custom(&ICON_RANGES),
+            size_ratio: 2.0 / 3.0,
+            glyph_offset: [0.0, 0.0],
+            monospace: true,
+        }
+    }
+
+    /// Material的字形底部与字身底部对齐，需下移到基线
+    pub fn material_symbols(path: impl Into<PathBuf>) -> IconFont {
+        IconFont {
+            set: IconSet::MaterialSymbols,
+            path: path.into(),
+            ranges: GlyphRanges::Custom(&ICON_RANGES),
+            size_ratio: 1.0,
+            glyph_offset: [0.0, 0.2],
+            monospace: true,
+        }
+    }
+}
+
+thread_local! {
+    /// 字体图集中合并的图标字体，[Icon]按此选择码点
+    static ICON_SET: Cell<IconSet> = const { Cell::new(IconSet::FontAwesome) };
+}
+
+pub(crate) fn set_icon_set(set: IconSet) {
+    ICON_SET.with(|current| current.set(set));
+}
+
+macro_rules! icons {
+    ($($name:ident = $awesome:literal, $material:literal;)*) => {
+        /// 常用图标，按合并的图标字体转换为字符，可直接用于文本
+        #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+        pub enum Icon {
+            $($name,)*
+        }
+
+        impl Icon {
+            pub const ALL: &'static [Icon] = &[$(Icon::$name,)*];
+
+            /// 图标在指定字体中的码点
+            pub fn codepoint(self, set: IconSet) -> u32 {
+                match (self, set) {
+                    $(
+                        (Icon::$name, IconSet::FontAwesome) => $awesome,
+                        (Icon::$name, IconSet::MaterialSymbols) => $material,
+                    )*
+                }
+            }
+        }
+    };
+}
+
+icons! {
+    Gear = 0xF013, 0xE8B8;
+    House = 0xF015, 0xE88A;
+    Search = 0xF002, 0xE8B6;
+    User = 0xF007, 0xE7FD;
+    Close = 0xF00D, 0xE5CD;
+    Check = 0xF00C, 0xE5CA;
+    Plus = 0xF067, 0xE145;
+    Minus = 0xF068, 0xE15B;
+    Trash = 0xF1F8, 0xE872;
+    Pen = 0xF304, 0xE3C9;
+    Save = 0xF0C7, 0xE161;
+    Folder = 0xF07B, 0xE2C7;
+    FolderOpen = 0xF07C, 0xE2C8;
+    File = 0xF15B, 0xE24D;
+    Copy = 0xF0C5, 0xE14D;
+    Link = 0xF0C1, 0xE157;
+    Play = 0xF04B, 0xE037;
+    Pause = 0xF04C, 0xE034;
+    Stop = 0xF04D, 0xE047;
+    Refresh = 0xF021, 0xE5D5;
+    Download = 0xF019, 0xE2C4;
+    Upload = 0xF093, 0xE2C6;
+    Eye = 0xF06E, 0xE8F4;
+    EyeSlash = 0xF070, 0xE8F5;
+    Lock = 0xF023, 0xE897;
+    Unlock = 0xF09C, 0xE898;
+    Bell = 0xF0F3, 0xE7F4;
+    Info = 0xF05A, 0xE88E;
+    Warning = 0xF071, 0xE002;
+    Question = 0xF059, 0xE887;
+    ArrowUp = 0xF062, 0xE5D8;
+    ArrowDown = 0xF063, 0xE5DB;
+    ArrowLeft = 0xF060, 0xE5C4;
+    ArrowRight = 0xF061, 0xE5C8;
+    ChevronUp = 0xF077, 0xE5CE;
+    ChevronDown = 0xF078, 0xE5CF;
+    ChevronLeft = 0xF053, 0xE5CB;
+    ChevronRight = 0xF054, 0xE5CC;
+    Bars = 0xF0C9, 0xE5D2;
+    Filter = 0xF0B0, 0xE152;
+    Star = 0xF005, 0xE838;
+    Heart = 0xF004, 0xE87D;
+    Power = 0xF011, 0xE8AC;
+    VolumeHigh = 0xF028, 0xE050;
+    VolumeMute = 0xF6A9, 0xE04F;
+    Keyboard = 0xF11C, 0xE312;
+    Mouse = 0xF8CC, 0xE323;
+    Gamepad = 0xF11B, 0xE338;
+    Desktop = 0xF390, 0xE30C;
+    Clock = 0xF017, 0xE8B5;
+    Wrench = 0xF0AD, 0xE869;
+    Palette = 0xF53F, 0xE40A;
+    Expand = 0xF065, 0xE5D0;
+    Compress = 0xF066, 0xE5D1;
+    Pin = 0xF08D, 0xF10D;
+}
+
+impl Icon {
+    /// 当前合并的图标字体中的字符
+    pub fn as_char(self) -> char {
+        let set = ICON_SET.with(|current| current.get());
+        char::from_u32(self.codepoint(set)).unwrap_or(char::REPLACEMENT_CHARACTER)
+    }
+}
+
+impl fmt::Display for Icon {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_char())
+    }
+}
+
+/// 图标控件
+pub trait IconUiExt {
+    /// 图标按钮，[label]为空或以`##`开头时只显示图标
+    fn icon_button(&self, icon: Icon, label: &str) -> bool;
+    /// 图标与文字
+    fn icon_text(&self, icon: Icon, text: &str);
+}
+
+impl IconUiExt for Ui {
+    fn icon_button(&self, icon: Icon, label: &str) -> bool {
+        self.button(icon_label(icon, label))
+    }
+
+    fn icon_text(&self, icon: Icon, text: &str) {
+        self.text(icon_label(icon, text));
+    }
+}
+
+fn icon_label(icon: Icon, label: &str) -> String {
+    if label.is_empty() || label.starts_with("##") {
+        format!("{icon}{label}")
+    } else {
+        format!("{icon} {label}")
+    }
+}
@@ -0,0 +1,279 @@
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+
+use imgui::internal::RawCast;
+use imgui::{Context, StyleColor, Ui};
+use windows::Win32::Foundation::{HWND, LPARAM, LRESULT, POINT, RECT, WPARAM};
+use windows::Win32::UI::Input::Ime::{
+    ImmAssociateContextEx, ImmGetCompositionStringW, ImmGetContext, ImmReleaseContext,
+    ImmSetCandidateWindow, ImmSetCompositionWindow, CANDIDATEFORM, CFS_EXCLUDE, CFS_POINT,
+    COMPOSITIONFORM, GCS_COMPSTR, GCS_CURSORPOS, HIMC, IACE_DEFAULT, IME_COMPOSITION_STRING,
+    ISC_SHOWUICOMPOSITIONWINDOW,
+};
+use windows::Win32::UI::WindowsAndMessaging::{
+    DefWindowProcW, WM_IME_COMPOSITION, WM_IME_ENDCOMPOSITION, WM_IME_SETCONTEXT,
+    WM_IME_STARTCOMPOSITION,
+};
+
+/// imgui对输入法的要求，文本框获得焦点时激活
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct ImeRequest {
+    /// 是否需要输入法
+    pub active: bool,
+    /// 光标左上角，覆盖窗口坐标
+    pub caret: [f32; 2],
+    /// 光标所在行的高度
+    pub line_height: f32,
+}
+
+/// 输入法正在组合、尚未提交的文本
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Composition {
+    pub text: String,
+    /// 组合光标位置，单位为字符
+    pub cursor: usize,
+}
+
+/// 输入法后端
+pub trait ImeBackend {
+    /// imgui的输入法要求变化时调用
+    fn update(&mut self, request: &ImeRequest);
+    /// 当前的组合文本，由覆盖层绘制在光标处
+    fn composition(&self) -> Option<Composition>;
+}
+
+thread_local! {
+    /// imgui在EndFrame中通过SetPlatformImeDataFn提交的要求
+    static REQUEST: Cell<Option<ImeRequest>> = const { Cell::new(None) };
+    static COMPOSITION: RefCell<Option<Composition>> = const { RefCell::new(None) };
+}
+
+/// 接收imgui的输入法要求
+pub(crate) fn install(imgui: &mut Context) {
+    unsafe extern "C" fn set_platform_ime_data(
+        _viewport: *mut imgui::sys::ImGuiViewport,
+        data: *mut imgui::sys::ImGuiPlatformImeData,
+    ) {
+        let data = &*data;
+        REQUEST.with(|request| {
+            request.set(Some(ImeRequest {
+                active: data.WantVisible,
+                caret: [data.InputPos.x, data.InputPos.y],
+                line_height: data.InputLineHeight,
+            }))
+        });
+    }
+    unsafe { imgui.io_mut().raw_mut().SetPlatformImeDataFn = Some(set_platform_ime_data) };
+}
+
+/// 取出imgui上一帧提交的要求，没有变化时返回None
+pub(crate) fn take_request() -> Option<ImeRequest> {
+    REQUEST.with(|request| request.take())
+}
+
+/// 在光标处绘制组合文本，带背景与下划线
+pub(crate) fn draw_composition(ui: &Ui, request: &ImeRequest, composition: &Composition) {
+    if composition.text.is_empty() {
+        return;
+    }
+    let draw_list = ui.get_foreground_draw_list();
+    let [x, y] = request.caret;
+    let size = ui.calc_text_size(&composition.text);
+    let height = size[1].max(request.line_height);
+    let style = ui.clone_style();
+    draw_list
+        .add_rect(
+            [x, y],
+            [x + size[0], y + height],
+            style[StyleColor::PopupBg],
+        )
+        .filled(true)
+        .build();
+    draw_list.add_text([x, y], style[StyleColor::Text], &composition.text);
+    draw_list
+        .add_line(
+            [x, y + height - 1.0],
+            [x + size[0], y + height - 1.0],
+            style[StyleColor::Text],
+        )
+        .build();
+    let before: String = composition.text.chars().take(composition.cursor).collect();
+    let caret = x + ui.calc_text_size(&before)[0];
+    draw_list
+        .add_line([caret, y], [caret, y + height], style[StyleColor::Text])
+        .build();
+}
+
+/// 处理输入法消息，返回Some时不再交给默认窗口过程
+pub(crate) fn handle_win32_message(
+    hwnd: HWND,
+    msg: u32,
+    wparam: WPARAM,
+    lparam: LPARAM,
+) -> Option<LRESULT> {
+    match msg {
+        WM_IME_SETCONTEXT => {
+            // 组合文本由覆盖层绘制，隐藏系统的组合窗口，保留候选窗口
+            let lparam = LPARAM(lparam.0 & !(ISC_SHOWUICOMPOSITIONWINDOW as isize));
+            Some(unsafe { DefWindowProcW(hwnd, msg, wparam, lparam) })
+        }
+        WM_IME_STARTCOMPOSITION => {
+            COMPOSITION.with(|c| *c.borrow_mut() = Some(Composition::default()));
+            // 不交给默认窗口过程，否则会显示系统的组合窗口
+            Some(LRESULT(0))
+        }
+        WM_IME_COMPOSITION => {
+            if lparam.0 as u32 & GCS_COMPSTR.0 != 0 {
+                let composition = read_composition(hwnd);
+                COMPOSITION.with(|c| *c.borrow_mut() = composition);
+            }
+            // 提交的文本由默认窗口过程转换为WM_CHAR
+            None
+        }
+        WM_IME_ENDCOMPOSITION => {
+            COMPOSITION.with(|c| *c.borrow_mut() = None);
+            None
+        }
+        _ => None,
+    }
+}
+
+fn read_composition(hwnd: HWND) -> Option<Composition> {
+    unsafe {
+        let himc = ImmGetContext(hwnd);
+        if himc.is_invalid() {
+            return None;
+        }
+        let text = composition_string(himc, GCS_COMPSTR);
+        let cursor = ImmGetCompositionStringW(himc, GCS_CURSORPOS, None, 0).max(0) as usize;
+        let _ = ImmReleaseContext(hwnd, himc);
+        let text = String::from_utf16_lossy(&text?);
+        // 光标位置以UTF-16为单位，转换为字符
+        let cursor = text
+            .char_indices()
+            .take_while(|(index, _)| text[..*index].encode_utf16().count() < cursor)
+            .count();
+        Some(Composition { text, cursor })
+    }
+}
+
+unsafe fn composition_string(himc: HIMC, kind: IME_COMPOSITION_STRING) -> Option<Vec<u16>> {
+    let bytes = ImmGetCompositionStringW(himc, kind, None, 0);
+    if bytes < 0 {
+        return None;
+    }
+    let mut buffer = vec![0u16; bytes as usize / 2];
+    ImmGetCompositionStringW(himc, kind, Some(buffer.as_mut_ptr() as _), bytes as u32);
+    Some(buffer)
+}
+
+/// Windows输入法(IMM32)
+pub struct Win32Ime {
+    hwnd: HWND,
+    active: bool,
+}
+
+impl Win32Ime {
+    /// 创建时关闭输入法，文本框获得焦点后再启用
+    pub fn new(hwnd: HWND) -> Win32Ime {
+        unsafe {
+            let _ = ImmAssociateContextEx(hwnd, HIMC::default(), 0);
+        }
+        Win32Ime {
+            hwnd,
+            active: false,
+        }
+    }
+}
+
+impl ImeBackend for Win32Ime {
+    fn update(&mut self, request: &ImeRequest) {
+        unsafe {
+            if request.active != self.active {
+                self.active = request.active;
+                // 关联空的输入上下文即可关闭输入法
+                let flags = if request.active { IACE_DEFAULT } else { 0 };
+                let _ = ImmAssociateContextEx(self.hwnd, HIMC::default(), flags);
+            }
+            if !request.active {
+                return;
+            }
+            let himc = ImmGetContext(self.hwnd);
+            if himc.is_invalid() {
+                return;
+            }
+            let [x, y] = request.caret;
+            let caret = POINT {
+                x: x as i32,
+                y: y as i32,
+            };
+            let composition = COMPOSITIONFORM {
+                dwStyle: CFS_POINT,
+                ptCurrentPos: caret,
+                ..Default::default()
+            };
+            let _ = ImmSetCompositionWindow(himc, &composition);
+            // 候选窗口显示在当前行下方，不遮挡输入行
+            let candidate = CANDIDATEFORM {
+                dwIndex: 0,
+                dwStyle: CFS_EXCLUDE,
+                ptCurrentPos: caret,
+                rcArea: RECT {
+                    left: caret.x,
+                    top: caret.y,
+                    right: caret.x + 1,
+                    bottom: (y + request.line_height) as i32,
+                },
+            };
+            let _ = ImmSetCandidateWindow(himc, &candidate);
+            let _ = ImmReleaseContext(self.hwnd, himc);
+        }
+    }
+
+    fn composition(&self) -> Option<Composition> {
+        COMPOSITION.with(|c| c.borrow().clone())
+    }
+}
+
+#[derive(Debug, Default)]
+struct MockImeState {
+    requests: Vec<ImeRequest>,
+    composition: Option<Composition>,
+}
+
+/// 由代码控制的输入法，记录收到的要求，克隆后共享同一状态
+#[derive(Clone, Debug, Default)]
+pub struct MockIme {
+    state: Rc<RefCell<MockImeState>>,
+}
+
+impl MockIme {
+    pub fn new() -> MockIme {
+        MockIme::default()
+    }
+
+    /// 设置组合文本，None表示结束组合
+    pub fn set_composition(&self, composition: Option<Composition>) {
+        self.state.borrow_mut().composition = composition;
+    }
+
+    /// 收到的所有要求
+    pub fn requests(&self) -> Vec<ImeRequest> {
+        self.state.borrow().requests.clone()
+    }
+
+    /// 最近一次要求
+    pub fn last_request(&self) -> Option<ImeRequest> {
+        self.state.borrow().requests.last().copied()
+    }
+}
+
+impl ImeBackend for MockIme {
+    fn update(&mut self, request: &ImeRequest) {
+        self.state.borrow_mut().requests.push(*request);
+    }
+
+    fn composition(&self) -> Option<Composition> {
+        self.state.borrow().composition.clone()
+    }
+}
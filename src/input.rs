@@ -0,0 +1,351 @@
+use std::cell::RefCell;
+
+use imgui::internal::RawCast;
+use imgui::{Io, Key, MouseButton};
+use windows::Win32::Foundation::{HWND, LPARAM, WPARAM};
+use windows::Win32::UI::Input::KeyboardAndMouse::{
+    GetCapture, GetKeyState, ReleaseCapture, SetCapture,
+};
+use windows::Win32::UI::WindowsAndMessaging::*;
+
+/// 与平台无关的输入事件
+#[derive(Clone, Debug, PartialEq)]
+pub enum InputEvent {
+    /// 鼠标移动，覆盖窗口坐标
+    MouseMove {
+        pos: [f32; 2],
+    },
+    MouseButton {
+        button: MouseButton,
+        down: bool,
+    },
+    /// 滚轮，`[横向, 纵向]`，单位为格
+    MouseWheel {
+        delta: [f32; 2],
+    },
+    Key {
+        key: Key,
+        down: bool,
+    },
+    /// 带模拟量的按键，例如手柄摇杆
+    KeyAnalog {
+        key: Key,
+        down: bool,
+        value: f32,
+    },
+    Text(char),
+    /// 覆盖窗口获得或失去焦点
+    Focus(bool),
+}
+
+impl InputEvent {
+    /// 转发给imgui
+    pub fn apply(&self, io: &mut Io) {
+        match *self {
+            InputEvent::MouseMove { pos } => io.add_mouse_pos_event(pos),
+            InputEvent::MouseButton { button, down } => io.add_mouse_button_event(button, down),
+            InputEvent::MouseWheel { delta } => io.add_mouse_wheel_event(delta),
+            InputEvent::Key { key, down } => io.add_key_event(key, down),
+            InputEvent::KeyAnalog { key, down, value } => io.add_key_analog_event(key, down, value),
+            InputEvent::Text(c) => io.add_input_character(c),
+            InputEvent::Focus(focused) => unsafe {
+                imgui::sys::ImGuiIO_AddFocusEvent(io.raw_mut(), focused)
+            },
+        }
+    }
+}
+
+/// 输入钩子，返回true表示事件已被消费，不再转发给imgui
+pub type InputHook = Box<dyn FnMut(&InputEvent) -> bool>;
+
+/// 收集平台输入事件，经应用钩子过滤后转发给imgui
+#[derive(Default)]
+pub struct InputPipeline {
+    pending: Vec<InputEvent>,
+    hook: Option<InputHook>,
+}
+
+impl InputPipeline {
+    /// 加入事件，下一次分发时转发
+    pub fn push(&mut self, event: InputEvent) {
+        self.pending.push(event);
+    }
+
+    pub fn extend(&mut self, events: impl IntoIterator<Item = InputEvent>) {
+        self.pending.extend(events);
+    }
+
+    /// 设置输入钩子，事件转发给imgui之前调用
+    pub fn set_hook(&mut self, hook: impl FnMut(&InputEvent) -> bool + 'static) {
+        self.hook = Some(Box::new(hook));
+    }
+
+    pub fn clear_hook(&mut self) {
+        self.hook = None;
+    }
+
+    /// 尚未分发的事件
+    pub fn pending(&self) -> &[InputEvent] {
+        &self.pending
+    }
+
+    /// 丢弃尚未分发的事件
+    pub fn clear(&mut self) {
+        self.pending.clear();
+    }
+
+    /// 将待处理事件转发给imgui，返回实际转发的事件
+    pub fn dispatch(&mut self, io: &mut Io) -> Vec<InputEvent> {
+        let mut events = std::mem::take(&mut self.pending);
+        if let Some(hook) = &mut self.hook {
+            events.retain(|event| !hook(event));
+        }
+        for event in &events {
+            event.apply(io);
+        }
+        events
+    }
+}
+
+#[derive(Default)]
+struct Win32Input {
+    events: Vec<InputEvent>,
+    /// 等待低位代理项的UTF-16高位代理项
+    high_surrogate: Option<u16>,
+}
+
+thread_local! {
+    static WIN32_INPUT: RefCell<Win32Input> = RefCell::new(Win32Input::default());
+}
+
+/// 取出窗口过程收集的输入事件
+pub(crate) fn take_win32_events() -> Vec<InputEvent> {
+    WIN32_INPUT.with(|input| std::mem::take(&mut input.borrow_mut().events))
+}
+
+/// 处理Win32输入消息，返回false表示不是输入消息
+pub(crate) fn handle_win32_message(hwnd: HWND, msg: u32, wparam: WPARAM, lparam: LPARAM) -> bool {
+    WIN32_INPUT.with(|input| {
+        let input = &mut *input.borrow_mut();
+        let events = &mut input.events;
+        match msg {
+            WM_MOUSEMOVE => events.push(InputEvent::MouseMove {
+                pos: [
+                    (lparam.0 & 0xFFFF) as i16 as f32,
+                    ((lparam.0 >> 16) & 0xFFFF) as i16 as f32,
+                ],
+            }),
+            WM_LBUTTONDOWN | WM_LBUTTONDBLCLK | WM_RBUTTONDOWN | WM_RBUTTONDBLCLK
+            | WM_MBUTTONDOWN | WM_MBUTTONDBLCLK | WM_XBUTTONDOWN | WM_XBUTTONDBLCLK => {
+                unsafe {
+                    if GetCapture().is_invalid() {
+                        let _ = SetCapture(hwnd);
+                    }
+                }
+                events.push(InputEvent::MouseButton {
+                    button: mouse_button(msg, wparam),
+                    down: true,
+                });
+            }
+            WM_LBUTTONUP | WM_RBUTTONUP | WM_MBUTTONUP | WM_XBUTTONUP => {
+                unsafe {
+                    if GetCapture() == hwnd {
+                        let _ = ReleaseCapture();
+                    }
+                }
+                events.push(InputEvent::MouseButton {
+                    button: mouse_button(msg, wparam),
+                    down: false,
+                });
+            }
+            WM_MOUSEWHEEL => events.push(InputEvent::MouseWheel {
+                delta: [0.0, wheel_delta(wparam)],
+            }),
+            WM_MOUSEHWHEEL => events.push(InputEvent::MouseWheel {
+                delta: [-wheel_delta(wparam), 0.0],
+            }),
+            WM_KEYDOWN | WM_KEYUP | WM_SYSKEYDOWN | WM_SYSKEYUP => {
+                let down = msg == WM_KEYDOWN || msg == WM_SYSKEYDOWN;
+                // 修饰键状态
+                let is_down = |vk: i32| unsafe { GetKeyState(vk) } as u16 & 0x8000 != 0;
+                for (key, down) in [
+                    (Key::ModCtrl, is_down(0x11)),
+                    (Key::ModShift, is_down(0x10)),
+                    (Key::ModAlt, is_down(0x12)),
+                    (Key::ModSuper, is_down(0x5B) || is_down(0x5C)),
+                ] {
+                    events.push(InputEvent::Key { key, down });
+                }
+                if let Some(key) = virtual_key_to_imgui(wparam.0, lparam.0) {
+                    events.push(InputEvent::Key { key, down });
+                }
+            }
+            WM_CHAR => {
+                let unit = wparam.0 as u16;
+                let c = match (input.high_surrogate.take(), unit) {
+                    (_, 0xD800..=0xDBFF) => {
+                        input.high_surrogate = Some(unit);
+                        None
+                    }
+                    (Some(high), 0xDC00..=0xDFFF) => {
+                        char::decode_utf16([high, unit]).next().and_then(|c| c.ok())
+                    }
+                    (_, unit) => char::from_u32(unit as u32),
+                };
+                if let Some(c) = c {
+                    events.push(InputEvent::Text(c));
+                }
+            }
+            WM_SETFOCUS => events.push(InputEvent::Focus(true)),
+            WM_KILLFOCUS => events.push(InputEvent::Focus(false)),
+            _ => return false,
+        }
+        true
+    })
+}
+
+fn mouse_button(msg: u32, wparam: WPARAM) -> MouseButton {
+    match msg {
+        WM_LBUTTONDOWN | WM_LBUTTONDBLCLK | WM_LBUTTONUP => MouseButton::Left,
+        WM_RBUTTONDOWN | WM_RBUTTONDBLCLK | WM_RBUTTONUP => MouseButton::Right,
+        WM_MBUTTONDOWN | WM_MBUTTONDBLCLK | WM_MBUTTONUP => MouseButton::Middle,
+        _ if (wparam.0 >> 16) & 0xFFFF == 1 => MouseButton::Extra1,
+        _ => MouseButton::Extra2,
+    }
+}
+
+fn wheel_delta(wparam: WPARAM) -> f32 {
+    ((wparam.0 >> 16) & 0xFFFF) as i16 as f32 / WHEEL_DELTA as f32
+}
+
+/// Windows虚拟键码转换为imgui按键
+fn virtual_key_to_imgui(vk: usize, lparam: isize) -> Option<Key> {
+    const LETTERS: [Key; 26] = [
+        Key::A,
+        Key::B,
+        Key::C,
+        Key::D,
+        Key::E,
+        Key::F,
+        Key::G,
+        Key::H,
+        Key::I,
+        Key::J,
+        Key::K,
+        Key::L,
+        Key::M,
+        Key::N,
+        Key::O,
+        Key::P,
+        Key::Q,
+        Key::R,
+        Key::S,
+        Key::T,
+        Key::U,
+        Key::V,
+        Key::W,
+        Key::X,
+        Key::Y,
+        Key::Z,
+    ];
+    const DIGITS: [Key; 10] = [
+        Key::Alpha0,
+        Key::Alpha1,
+        Key::Alpha2,
+        Key::Alpha3,
+        Key::Alpha4,
+        Key::Alpha5,
+        Key::Alpha6,
+        Key::Alpha7,
+        Key::Alpha8,
+        Key::Alpha9,
+    ];
+    const KEYPAD: [Key; 10] = [
+        Key::Keypad0,
+        Key::Keypad1,
+        Key::Keypad2,
+        Key::Keypad3,
+        Key::Keypad4,
+        Key::Keypad5,
+        Key::Keypad6,
+        Key::Keypad7,
+        Key::Keypad8,
+        Key::Keypad9,
+    ];
+    const FUNCTION: [Key; 12] = [
+        Key::F1,
+        Key::F2,
+        Key::F3,
+        Key::F4,
+        Key::F5,
+        Key::F6,
+        Key::F7,
+        Key::F8,
+        Key::F9,
+        Key::F10,
+        Key::F11,
+        Key::F12,
+    ];
+    let extended = lparam & (1 << 24) != 0;
+    let scancode = (lparam >> 16) & 0xFF;
+    Some(match vk {
+        0x41..=0x5A => LETTERS[vk - 0x41],
+        0x30..=0x39 => DIGITS[vk - 0x30],
+        0x60..=0x69 => KEYPAD[vk - 0x60],
+        0x70..=0x7B => FUNCTION[vk - 0x70],
+        0x09 => Key::Tab,
+        0x25 => Key::LeftArrow,
+        0x27 => Key::RightArrow,
+        0x26 => Key::UpArrow,
+        0x28 => Key::DownArrow,
+        0x21 => Key::PageUp,
+        0x22 => Key::PageDown,
+        0x24 => Key::Home,
+        0x23 => Key::End,
+        0x2D => Key::Insert,
+        0x2E => Key::Delete,
+        0x08 => Key::Backspace,
+        0x20 => Key::Space,
+        0x0D if extended => Key::KeypadEnter,
+        0x0D => Key::Enter,
+        0x1B => Key::Escape,
+        0xDE => Key::Apostrophe,
+        0xBC => Key::Comma,
+        0xBD => Key::Minus,
+        0xBE => Key::Period,
+        0xBF => Key::Slash,
+        0xBA => Key::Semicolon,
+        0xBB => Key::Equal,
+        0xDB => Key::LeftBracket,
+        0xDC => Key::Backslash,
+        0xDD => Key::RightBracket,
+        0xC0 => Key::GraveAccent,
+        0x14 => Key::CapsLock,
+        0x91 => Key::ScrollLock,
+        0x90 => Key::NumLock,
+        0x2C => Key::PrintScreen,
+        0x13 => Key::Pause,
+        0x6E => Key::KeypadDecimal,
+        0x6F => Key::KeypadDivide,
+        0x6A => Key::KeypadMultiply,
+        0x6D => Key::KeypadSubtract,
+        0x6B => Key::KeypadAdd,
+        // 右Shift的扫描码为0x36
+        0x10 if scancode == 0x36 => Key::RightShift,
+        0x10 => Key::LeftShift,
+        0x11 if extended => Key::RightCtrl,
+        0x11 => Key::LeftCtrl,
+        0x12 if extended => Key::RightAlt,
+        0x12 => Key::LeftAlt,
+        0xA0 => Key::LeftShift,
+        0xA1 => Key::RightShift,
+        0xA2 => Key::LeftCtrl,
+        0xA3 => Key::RightCtrl,
+        0xA4 => Key::LeftAlt,
+        0xA5 => Key::RightAlt,
+        0x5B => Key::LeftSuper,
+        0x5C => Key::RightSuper,
+        0x5D => Key::Menu,
+        _ => return None,
+    })
+}
@@ -0,0 +1,289 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::ffi::OsString;
+use std::fs;
+use std::io;
+use std::os::windows::ffi::OsStringExt;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::time::Duration;
+
+use imgui::Context;
+use windows::core::PCWSTR;
+use windows::Win32::Foundation::HMODULE;
+use windows::Win32::System::LibraryLoader::{
+    GetModuleFileNameW, GetModuleHandleExW, GET_MODULE_HANDLE_EX_FLAG_FROM_ADDRESS,
+    GET_MODULE_HANDLE_EX_FLAG_UNCHANGED_REFCOUNT,
+};
+
+/// imgui布局(窗口位置、大小、折叠状态等ini设置)的存储
+pub trait LayoutStorage {
+    /// 读取[key]对应的布局，尚未保存过时返回None
+    fn load(&self, key: &str) -> io::Result<Option<String>>;
+    fn save(&self, key: &str, data: &str) -> io::Result<()>;
+}
+
+/// 每个目标一个ini文件
+#[derive(Clone, Debug)]
+pub struct FileLayoutStorage {
+    dir: PathBuf,
+}
+
+impl FileLayoutStorage {
+    pub fn new(dir: impl Into<PathBuf>) -> FileLayoutStorage {
+        FileLayoutStorage { dir: dir.into() }
+    }
+
+    /// 覆盖层所在的exe或dll旁的`layouts`目录
+    pub fn next_to_exe() -> FileLayoutStorage {
+        let dir = module_path()
+            .or_else(|| std::env::current_exe().ok())
+            .and_then(|path| path.parent().map(Path::to_path_buf))
+            .unwrap_or_default();
+        FileLayoutStorage::new(dir.join("layouts"))
+    }
+
+    /// 当前用户的配置目录，`%APPDATA%\[app]\layouts`
+    pub fn user_config(app: &str) -> io::Result<FileLayoutStorage> {
+        let appdata = std::env::var_os("APPDATA")
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "APPDATA is not set"))?;
+        Ok(FileLayoutStorage::new(
+            PathBuf::from(appdata).join(app).join("layouts"),
+        ))
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    pub fn path(&self, key: &str) -> PathBuf {
+        self.dir.join(format!("{}.ini", file_name(key)))
+    }
+}
+
+impl LayoutStorage for FileLayoutStorage {
+    fn load(&self, key: &str) -> io::Result<Option<String>> {
+        match fs::read_to_string(self.path(key)) {
+            Ok(data) => Ok(Some(data)),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    fn save(&self, key: &str, data: &str) -> io::Result<()> {
+        fs::create_dir_all(&self.dir)?;
+        let path = self.path(key);
+        // 先写临时文件再替换，退出时被中断也不会留下半个文件
+        let temp = path.with_extension("ini.tmp");
+        fs::write(&temp, data)?;
+        fs::rename(&temp, &path)
+    }
+}
+
+/// 保存在内存中，克隆之间共享数据，用于测试
+#[derive(Clone, Debug, Default)]
+pub struct MemoryLayoutStorage {
+    layouts: Rc<RefCell<HashMap<String, String>>>,
+}
+
+impl MemoryLayoutStorage {
+    pub fn new() -> MemoryLayoutStorage {
+        MemoryLayoutStorage::default()
+    }
+
+    pub fn get(&self, key: &str) -> Option<String> {
+        self.layouts.borrow().get(key).cloned()
+    }
+
+    pub fn insert(&self, key: impl Into<String>, data: impl Into<String>) {
+        self.layouts.borrow_mut().insert(key.into(), data.into());
+    }
+}
+
+impl LayoutStorage for MemoryLayoutStorage {
+    fn load(&self, key: &str) -> io::Result<Option<String>> {
+        Ok(self.get(key))
+    }
+
+    fn save(&self, key: &str, data: &str) -> io::Result<()> {
+        self.insert(key, data);
+        Ok(())
+    }
+}
+
+/// 布局持久化
+#[derive(Clone)]
+pub struct LayoutOptions {
+    pub storage: Rc<dyn LayoutStorage>,
+    /// 布局的键，None时按覆盖目标生成，例如目标进程的exe名称
+    pub key: Option<String>,
+    /// 布局变化后等待多久再保存，退出时总会保存
+    pub save_interval: Duration,
+}
+
+impl LayoutOptions {
+    pub fn new(storage: impl LayoutStorage + 'static) -> LayoutOptions {
+        LayoutOptions {
+            storage: Rc::new(storage),
+            key: None,
+            save_interval: Duration::from_secs(5),
+        }
+    }
+}
+
+/// 保存到[FileLayoutStorage::next_to_exe]
+impl Default for LayoutOptions {
+    fn default() -> LayoutOptions {
+        LayoutOptions::new(FileLayoutStorage::next_to_exe())
+    }
+}
+
+/// 按键读写当前imgui上下文的布局
+pub(crate) struct Layout {
+    storage: Rc<dyn LayoutStorage>,
+    key: String,
+}
+
+impl Layout {
+    pub(crate) fn new(options: &LayoutOptions, target_key: String) -> Layout {
+        Layout {
+            storage: options.storage.clone(),
+            key: options.key.clone().unwrap_or(target_key),
+        }
+    }
+
+    pub(crate) fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// 须在第一帧之前调用
+    pub(crate) fn load(&self, imgui: &mut Context) {
+        match self.storage.load(&self.key) {
+            Ok(Some(data)) => {
+                log::debug!("Loaded layout {:?}", self.key);
+                imgui.load_ini_settings(&data);
+            }
+            Ok(None) => {}
+            Err(e) => log::warn!("Failed to load layout {:?}: {e}", self.key),
+        }
+    }
+
+    pub(crate) fn save(&self, imgui: &mut Context) -> io::Result<()> {
+        let mut data = String::new();
+        imgui.save_ini_settings(&mut data);
+        imgui.io_mut().want_save_ini_settings = false;
+        self.storage.save(&self.key, &data)
+    }
+
+    /// imgui在布局变化并经过[LayoutOptions::save_interval]后请求保存
+    pub(crate) fn save_if_requested(&self, imgui: &mut Context) {
+        if !imgui.io().want_save_ini_settings {
+            return;
+        }
+        if let Err(e) = self.save(imgui) {
+            log::warn!("Failed to save layout {:?}: {e}", self.key);
+        }
+    }
+}
+
+/// 键中不能用于文件名的字符替换为`_`
+fn file_name(key: &str) -> String {
+    key.chars()
+        .map(|c| {
+            if c.is_alphanumeric() || matches!(c, '-' | '_' | '.') {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+/// 包含本函数的模块的路径，作为dll注入时为dll本身
+fn module_path() -> Option<PathBuf> {
+    let mut module = HMODULE::default();
+    unsafe {
+        GetModuleHandleExW(
+            GET_MODULE_HANDLE_EX_FLAG_FROM_ADDRESS | GET_MODULE_HANDLE_EX_FLAG_UNCHANGED_REFCOUNT,
+            PCWSTR(module_path as *const () as *const u16),
+            &mut module,
+        )
+        .ok()?;
+    }
+    let mut buffer = vec![0u16; 1024];
+    let len = unsafe { GetModuleFileNameW(Some(module), &mut buffer) } as usize;
+    if len == 0 || len >= buffer.len() {
+        return None;
+    }
+    Some(PathBuf::from(OsString::from_wide(&buffer[..len])))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use super::*;
+
+    /// imgui同一时间只能有一个上下文
+    static IMGUI: Mutex<()> = Mutex::new(());
+
+    const INI: &str = "[Window][Tools]\nPos=40,60\nSize=320,200\nCollapsed=0\n\n";
+
+    fn with_context(f: impl FnOnce(&mut Context)) {
+        let _guard = IMGUI.lock().unwrap_or_else(|e| e.into_inner());
+        let mut imgui = Context::create();
+        imgui.set_ini_filename(None);
+        f(&mut imgui);
+    }
+
+    #[test]
+    fn save_then_load() {
+        let storage = MemoryLayoutStorage::new();
+        let layout = Layout::new(&LayoutOptions::new(storage.clone()), "game.exe".into());
+        with_context(|imgui| {
+            imgui.load_ini_settings(INI);
+            layout.save(imgui).unwrap();
+        });
+        let saved = storage.get("game.exe").unwrap();
+        assert!(saved.contains("[Window][Tools]\nPos=40,60\nSize=320,200"));
+
+        with_context(|imgui| {
+            layout.load(imgui);
+            let mut data = String::new();
+            imgui.save_ini_settings(&mut data);
+            assert_eq!(data, saved);
+        });
+    }
+
+    #[test]
+    fn save_only_when_requested() {
+        let storage = MemoryLayoutStorage::new();
+        let layout = Layout::new(&LayoutOptions::new(storage.clone()), "game.exe".into());
+        with_context(|imgui| {
+            imgui.load_ini_settings(INI);
+            layout.save_if_requested(imgui);
+            assert_eq!(storage.get("game.exe"), None);
+            imgui.io_mut().want_save_ini_settings = true;
+            layout.save_if_requested(imgui);
+            assert!(!imgui.io().want_save_ini_settings);
+        });
+        assert!(storage.get("game.exe").is_some());
+    }
+
+    #[test]
+    fn key_overrides_target() {
+        let mut options = LayoutOptions::new(MemoryLayoutStorage::new());
+        assert_eq!(Layout::new(&options, "game.exe".into()).key(), "game.exe");
+        options.key = Some("shared".into());
+        assert_eq!(Layout::new(&options, "game.exe".into()).key(), "shared");
+    }
+
+    #[test]
+    fn file_names_are_sanitized() {
+        assert_eq!(file_name("game.exe"), "game.exe");
+        assert_eq!(file_name("C:\\Games\\a b?.exe"), "C__Games_a_b_.exe");
+        assert_eq!(file_name("游戏-1"), "游戏-1");
+        let storage = FileLayoutStorage::new("layouts");
+        assert_eq!(storage.path("a/b"), Path::new("layouts").join("a_b.ini"));
+    }
+}
@@ -1,6 +1,58 @@
 pub mod window;
+pub use canvas::{CanvasMapping, FitMode, VirtualCanvas};
+pub use clipboard::{MemoryClipboard, Win32Clipboard};
+#[cfg(all(unix, feature = "x11"))]
+pub use clipboard::{X11Clipboard, X11Selection};
+pub use font_cache::FontAtlasCache;
+pub use fonts::{
+    system_font_dirs, FontFace, FontManager, FontOptions, FontRole, FontRoles, FontSpec,
+    GlyphRanges,
+};
+pub use frame::{FrameContext, StyleHandle};
+#[cfg(feature = "gamepad")]
+pub use gamepad::GilrsSource;
+pub use gamepad::{
+    GamepadButton, GamepadOptions, GamepadSource, GamepadState, MockGamepad, XInputSource,
+};
+pub use glyphs::DynamicGlyphs;
+pub use hit_test::{HitRegions, Region};
+pub use hotkey::{Chord, Hotkeys, Key, KeySource, Modifiers, ParseChordError, Win32KeySource};
+pub use icons::{Icon, IconFont, IconSet, IconUiExt};
+pub use ime::{Composition, ImeBackend, ImeRequest, MockIme, Win32Ime};
+pub use input::{InputEvent, InputHook, InputPipeline};
+pub use layout::{FileLayoutStorage, LayoutOptions, LayoutStorage, MemoryLayoutStorage};
+pub use message::{
+    MessageHook, MessageHookId, MessageStage, RawMessage, WindowEvent, WindowMessage,
+};
+pub use monitor::{
+    monitors, primary_monitor, virtual_desktop_bounds, MonitorInfo, MonitorSelector,
+};
+pub use record::{InputCapture, InputRecording, InputReplay, RecordedFrame};
+pub use theme::{Theme, ThemeBase, ThemeFont, ThemeFonts, ThemeRounding, ThemeSizes};
+pub use tracking::{TrackingMode, TrackingOptions};
+pub use transition::{lerp_style, Easing, Transition};
 pub type Result<T> = windows::core::Result<T>;
-pub use window_tracker::OverlayTarget;
 pub use window_tracker::WINDOWS_RECT;
+pub use window_tracker::{AnchorRect, Margins, OverlayAnchor, OverlayTarget};
+mod canvas;
+mod clipboard;
+mod cursor;
 mod d3d11;
+mod font_cache;
+mod fonts;
+mod frame;
+mod gamepad;
+mod glyphs;
+mod hit_test;
+mod hotkey;
+mod icons;
+mod ime;
+mod input;
+mod layout;
+mod message;
+mod monitor;
+mod record;
+mod theme;
+mod tracking;
+mod transition;
 mod window_tracker;
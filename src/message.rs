@@ -0,0 +1,174 @@
+use std::cell::RefCell;
+use std::path::PathBuf;
+
+use windows::Win32::Foundation::{HWND, LPARAM, LRESULT, POINT, WPARAM};
+use windows::Win32::UI::Shell::{DragQueryFileW, DragQueryPoint, HDROP};
+use windows::Win32::UI::WindowsAndMessaging::{
+    PBT_APMRESUMEAUTOMATIC, PBT_APMSUSPEND, WA_INACTIVE, WM_ACTIVATE, WM_DISPLAYCHANGE,
+    WM_DROPFILES, WM_POWERBROADCAST, WM_WTSSESSION_CHANGE, WTS_SESSION_LOCK, WTS_SESSION_UNLOCK,
+};
+
+/// 钩子在窗口过程中的调用时机
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MessageStage {
+    /// imgui与输入管线处理之前，消费后imgui不会收到该消息
+    BeforeImgui,
+    /// imgui未处理的消息，在默认处理之前调用
+    AfterImgui,
+}
+
+/// 平台无关的窗口事件
+#[derive(Clone, Debug, PartialEq)]
+pub enum WindowEvent {
+    /// 文件拖放到覆盖窗口，需开启[crate::window::WindowsOptions::accept_dropped_files]
+    FilesDropped {
+        paths: Vec<PathBuf>,
+        /// 放下的位置，覆盖窗口坐标
+        pos: [f32; 2],
+    },
+    /// 系统即将睡眠
+    Suspend,
+    /// 系统从睡眠中恢复
+    Resume,
+    SessionLocked,
+    SessionUnlocked,
+    /// 显示器配置变化
+    DisplayChanged,
+    /// 覆盖窗口激活或失去激活
+    Activated(bool),
+}
+
+/// 原始Win32消息
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RawMessage {
+    pub hwnd: HWND,
+    pub msg: u32,
+    pub wparam: WPARAM,
+    pub lparam: LPARAM,
+}
+
+/// 窗口消息，平台无关的事件与原始消息
+#[derive(Clone, Debug, PartialEq)]
+pub struct WindowMessage {
+    /// 已识别的事件，其它消息为None
+    pub event: Option<WindowEvent>,
+    pub raw: RawMessage,
+}
+
+impl WindowMessage {
+    fn new(raw: RawMessage) -> WindowMessage {
+        WindowMessage {
+            event: window_event(&raw),
+            raw,
+        }
+    }
+}
+
+fn window_event(raw: &RawMessage) -> Option<WindowEvent> {
+    Some(match raw.msg {
+        WM_DROPFILES => unsafe {
+            let hdrop = HDROP(raw.wparam.0 as _);
+            let count = DragQueryFileW(hdrop, u32::MAX, None);
+            let paths = (0..count)
+                .map(|index| {
+                    let len = DragQueryFileW(hdrop, index, None) as usize;
+                    let mut buffer = vec![0u16; len + 1];
+                    DragQueryFileW(hdrop, index, Some(&mut buffer));
+                    PathBuf::from(String::from_utf16_lossy(&buffer[..len]))
+                })
+                .collect();
+            let mut point = POINT::default();
+            let _ = DragQueryPoint(hdrop, &mut point);
+            WindowEvent::FilesDropped {
+                paths,
+                pos: [point.x as f32, point.y as f32],
+            }
+        },
+        WM_POWERBROADCAST => match raw.wparam.0 as u32 {
+            PBT_APMSUSPEND => WindowEvent::Suspend,
+            PBT_APMRESUMEAUTOMATIC => WindowEvent::Resume,
+            _ => return None,
+        },
+        WM_WTSSESSION_CHANGE => match raw.wparam.0 as u32 {
+            WTS_SESSION_LOCK => WindowEvent::SessionLocked,
+            WTS_SESSION_UNLOCK => WindowEvent::SessionUnlocked,
+            _ => return None,
+        },
+        WM_DISPLAYCHANGE => WindowEvent::DisplayChanged,
+        WM_ACTIVATE => WindowEvent::Activated((raw.wparam.0 & 0xFFFF) as u32 != WA_INACTIVE),
+        _ => return None,
+    })
+}
+
+/// 消息钩子，返回Some时消费该消息并作为窗口过程的返回值
+pub type MessageHook = Box<dyn FnMut(&WindowMessage) -> Option<isize>>;
+
+/// 注册的钩子，用于移除
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct MessageHookId(u64);
+
+#[derive(Default)]
+struct Hooks {
+    next_id: u64,
+    hooks: Vec<(MessageHookId, MessageStage, MessageHook)>,
+}
+
+thread_local! {
+    static HOOKS: RefCell<Hooks> = RefCell::new(Hooks::default());
+}
+
+/// 注册钩子，钩子在创建窗口的线程上调用
+pub(crate) fn add_hook(stage: MessageStage, hook: MessageHook) -> MessageHookId {
+    HOOKS.with(|hooks| {
+        let mut hooks = hooks.borrow_mut();
+        hooks.next_id += 1;
+        let id = MessageHookId(hooks.next_id);
+        hooks.hooks.push((id, stage, hook));
+        id
+    })
+}
+
+pub(crate) fn remove_hook(id: MessageHookId) -> bool {
+    HOOKS.with(|hooks| {
+        let hooks = &mut hooks.borrow_mut().hooks;
+        let len = hooks.len();
+        hooks.retain(|(hook_id, _, _)| *hook_id != id);
+        hooks.len() != len
+    })
+}
+
+pub(crate) fn clear_hooks() {
+    HOOKS.with(|hooks| hooks.borrow_mut().hooks.clear());
+}
+
+/// 按注册顺序调用该阶段的钩子，第一个消费消息的钩子的结果作为返回值
+pub(crate) fn dispatch(
+    stage: MessageStage,
+    hwnd: HWND,
+    msg: u32,
+    wparam: WPARAM,
+    lparam: LPARAM,
+) -> Option<LRESULT> {
+    // 钩子内可能再次触发窗口过程，调用期间先取出钩子，嵌套的消息不会经过钩子
+    let mut taken = HOOKS.with(|hooks| std::mem::take(&mut hooks.borrow_mut().hooks));
+    if taken.is_empty() {
+        return None;
+    }
+    let message = WindowMessage::new(RawMessage {
+        hwnd,
+        msg,
+        wparam,
+        lparam,
+    });
+    let result = taken
+        .iter_mut()
+        .filter(|(_, hook_stage, _)| *hook_stage == stage)
+        .find_map(|(_, _, hook)| hook(&message));
+    HOOKS.with(|hooks| {
+        let hooks = &mut hooks.borrow_mut().hooks;
+        // 保留调用期间新注册的钩子
+        taken.append(hooks);
+        *hooks = taken;
+    });
+    result.map(LRESULT)
+}
@@ -0,0 +1,120 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use windows::core::BOOL;
+use windows::Win32::Foundation::{LPARAM, RECT, TRUE};
+use windows::Win32::Graphics::Gdi::{
+    EnumDisplayMonitors, GetMonitorInfoW, HDC, HMONITOR, MONITORINFO, MONITORINFOEXW,
+};
+use windows::Win32::UI::WindowsAndMessaging::{
+    GetSystemMetrics, MONITORINFOF_PRIMARY, SM_CXVIRTUALSCREEN, SM_CYVIRTUALSCREEN,
+    SM_XVIRTUALSCREEN, SM_YVIRTUALSCREEN,
+};
+
+/// 显示器配置发生变化(插拔、分辨率调整)，由窗口过程在收到`WM_DISPLAYCHANGE`时设置
+pub(crate) static DISPLAY_CHANGED: AtomicBool = AtomicBool::new(false);
+
+/// 显示器信息
+#[derive(Clone, Debug)]
+pub struct MonitorInfo {
+    pub handle: HMONITOR,
+    /// 设备名称，例如`\\.\DISPLAY1`
+    pub name: String,
+    /// 显示器区域(虚拟桌面坐标)
+    pub bounds: RECT,
+    /// 工作区域(不含任务栏)
+    pub work_area: RECT,
+    /// 是否为主显示器
+    pub primary: bool,
+}
+
+/// 选择显示器的方式
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum MonitorSelector {
+    /// 按[monitors]返回的顺序选择
+    Index(usize),
+    /// 按设备名称选择，`DISPLAY2`与`\\.\DISPLAY2`均可，不区分大小写
+    Name(String),
+}
+
+impl MonitorSelector {
+    /// 在显示器列表中查找
+    pub fn find<'a>(&self, monitors: &'a [MonitorInfo]) -> Option<&'a MonitorInfo> {
+        match self {
+            MonitorSelector::Index(index) => monitors.get(*index),
+            MonitorSelector::Name(name) => {
+                let name = name.trim_start_matches(r"\\.\").to_ascii_uppercase();
+                monitors.iter().find(|monitor| {
+                    monitor
+                        .name
+                        .trim_start_matches(r"\\.\")
+                        .to_ascii_uppercase()
+                        == name
+                })
+            }
+        }
+    }
+}
+
+/// 枚举所有显示器
+pub fn monitors() -> Vec<MonitorInfo> {
+    unsafe extern "system" fn callback(
+        monitor: HMONITOR,
+        _hdc: HDC,
+        _rect: *mut RECT,
+        data: LPARAM,
+    ) -> BOOL {
+        let monitors = &mut *(data.0 as *mut Vec<MonitorInfo>);
+        let mut info = MONITORINFOEXW::default();
+        info.monitorInfo.cbSize = size_of::<MONITORINFOEXW>() as u32;
+        if GetMonitorInfoW(monitor, &mut info as *mut _ as *mut MONITORINFO).as_bool() {
+            let len = info
+                .szDevice
+                .iter()
+                .position(|c| *c == 0)
+                .unwrap_or(info.szDevice.len());
+            monitors.push(MonitorInfo {
+                handle: monitor,
+                name: String::from_utf16_lossy(&info.szDevice[..len]),
+                bounds: info.monitorInfo.rcMonitor,
+                work_area: info.monitorInfo.rcWork,
+                primary: info.monitorInfo.dwFlags & MONITORINFOF_PRIMARY != 0,
+            });
+        }
+        TRUE
+    }
+
+    let mut monitors: Vec<MonitorInfo> = Vec::new();
+    unsafe {
+        let _ = EnumDisplayMonitors(
+            None,
+            None,
+            Some(callback),
+            LPARAM(&mut monitors as *mut _ as isize),
+        );
+    }
+    monitors
+}
+
+/// 主显示器
+pub fn primary_monitor() -> Option<MonitorInfo> {
+    monitors().into_iter().find(|monitor| monitor.primary)
+}
+
+/// 覆盖所有显示器的虚拟桌面区域
+pub fn virtual_desktop_bounds() -> RECT {
+    unsafe {
+        let left = GetSystemMetrics(SM_XVIRTUALSCREEN);
+        let top = GetSystemMetrics(SM_YVIRTUALSCREEN);
+        RECT {
+            left,
+            top,
+            right: left + GetSystemMetrics(SM_CXVIRTUALSCREEN),
+            bottom: top + GetSystemMetrics(SM_CYVIRTUALSCREEN),
+        }
+    }
+}
+
+/// 取出并清除显示器变化标记
+pub(crate) fn take_display_changed() -> bool {
+    DISPLAY_CHANGED.swap(false, Ordering::AcqRel)
+}
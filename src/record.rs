@@ -0,0 +1,344 @@
+use std::fmt::Write as _;
+use std::fs::{self, File};
+use std::io::{self, Write as _};
+use std::path::{Path, PathBuf};
+
+use imgui::{Context, Key, MouseButton, Ui};
+
+use crate::hotkey::{self, KeySource};
+use crate::input::InputEvent;
+use crate::window::OverlayMode;
+
+/// 录制文件头
+const HEADER: &str = "imgui-overlay-input 1";
+
+/// 一帧的输入与帧间隔
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct RecordedFrame {
+    /// 帧间隔，单位为秒
+    pub delta_time: f32,
+    /// 覆盖窗口大小，为0时回放使用实际大小
+    pub display_size: [f32; 2],
+    /// 交互模式
+    pub mode: Option<OverlayMode>,
+    /// 处于按下状态的按键，回放时用于更新热键
+    pub keys_down: Vec<hotkey::Key>,
+    /// 该帧的输入事件(输入钩子过滤前)
+    pub events: Vec<InputEvent>,
+}
+
+/// 录制的输入与帧间隔，运行时通过[InputCapture::Record]录制，[std::fmt::Display]输出录制文本
+#[derive(Clone, Debug, Default)]
+pub struct InputRecording {
+    frames: Vec<RecordedFrame>,
+}
+
+impl InputRecording {
+    pub fn new() -> InputRecording {
+        InputRecording::default()
+    }
+
+    pub fn push_frame(&mut self, frame: RecordedFrame) {
+        self.frames.push(frame);
+    }
+
+    pub fn frames(&self) -> &[RecordedFrame] {
+        &self.frames
+    }
+
+    /// 开始回放
+    pub fn replay(self) -> InputReplay {
+        InputReplay {
+            frames: self.frames,
+            position: 0,
+        }
+    }
+
+    /// 读取录制文件
+    pub fn load(path: impl AsRef<Path>) -> io::Result<InputRecording> {
+        Self::parse(&fs::read_to_string(path)?)
+    }
+
+    /// 解析录制文本，每帧以`frame <间隔>`开头，之后每行一个状态或事件
+    pub fn parse(text: &str) -> io::Result<InputRecording> {
+        let mut lines = text.lines().enumerate();
+        match lines.next() {
+            Some((_, header)) if header.trim() == HEADER => {}
+            _ => return Err(invalid_data(format!("missing header `{HEADER}`"))),
+        }
+        let mut frames: Vec<RecordedFrame> = Vec::new();
+        for (index, line) in lines {
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            let error = || invalid_data(format!("line {}: invalid record `{line}`", index + 1));
+            let mut parts = line.split_whitespace();
+            let kind = parts.next().unwrap_or_default();
+            let args: Vec<&str> = parts.collect();
+            if kind == "frame" {
+                let delta_time = args
+                    .first()
+                    .and_then(|s| s.parse().ok())
+                    .ok_or_else(error)?;
+                frames.push(RecordedFrame {
+                    delta_time,
+                    ..RecordedFrame::default()
+                });
+                continue;
+            }
+            let frame = frames.last_mut().ok_or_else(error)?;
+            match kind {
+                "display" => {
+                    let size = |index: usize| args.get(index)?.parse::<f32>().ok();
+                    frame.display_size = [size(0).ok_or_else(error)?, size(1).ok_or_else(error)?];
+                }
+                "mode" => {
+                    frame.mode = Some(args.first().and_then(|s| parse_mode(s)).ok_or_else(error)?);
+                }
+                "keys" => {
+                    frame.keys_down = args
+                        .iter()
+                        .map(|name| hotkey::Key::from_name(name))
+                        .collect::<Option<_>>()
+                        .ok_or_else(error)?;
+                }
+                _ => frame
+                    .events
+                    .push(parse_event(kind, &args).ok_or_else(error)?),
+            }
+        }
+        Ok(InputRecording { frames })
+    }
+}
+
+impl std::fmt::Display for InputRecording {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut text = String::new();
+        writeln!(text, "{HEADER}")?;
+        for frame in &self.frames {
+            write_frame(&mut text, frame)?;
+        }
+        f.write_str(&text)
+    }
+}
+
+fn write_frame(text: &mut String, frame: &RecordedFrame) -> std::fmt::Result {
+    writeln!(text, "frame {}", frame.delta_time)?;
+    if frame.display_size != [0.0, 0.0] {
+        let [width, height] = frame.display_size;
+        writeln!(text, "display {width} {height}")?;
+    }
+    if let Some(mode) = frame.mode {
+        writeln!(text, "mode {mode:?}")?;
+    }
+    if !frame.keys_down.is_empty() {
+        let names: Vec<&str> = frame.keys_down.iter().map(|key| key.name()).collect();
+        writeln!(text, "keys {}", names.join(" "))?;
+    }
+    for event in &frame.events {
+        match event {
+            InputEvent::MouseMove { pos } => writeln!(text, "move {} {}", pos[0], pos[1]),
+            InputEvent::MouseButton { button, down } => {
+                writeln!(text, "button {button:?} {}", *down as u8)
+            }
+            InputEvent::MouseWheel { delta } => {
+                writeln!(text, "wheel {} {}", delta[0], delta[1])
+            }
+            InputEvent::Key { key, down } => writeln!(text, "key {key:?} {}", *down as u8),
+            InputEvent::KeyAnalog { key, down, value } => {
+                writeln!(text, "analog {key:?} {} {value}", *down as u8)
+            }
+            // 按码点记录，避免空白字符
+            InputEvent::Text(c) => writeln!(text, "text {}", *c as u32),
+            InputEvent::Focus(focused) => writeln!(text, "focus {}", *focused as u8),
+        }?;
+    }
+    Ok(())
+}
+
+/// 边录制边追加到文件，进程崩溃或被结束时已录制的帧不会丢失
+pub(crate) struct RecordingWriter {
+    path: PathBuf,
+    file: File,
+}
+
+impl RecordingWriter {
+    pub(crate) fn create(path: &Path) -> io::Result<RecordingWriter> {
+        let mut file = File::create(path)?;
+        writeln!(file, "{HEADER}")?;
+        Ok(RecordingWriter {
+            path: path.to_path_buf(),
+            file,
+        })
+    }
+
+    pub(crate) fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// 每帧直接写入文件，不经过用户态缓冲
+    pub(crate) fn write_frame(&mut self, frame: &RecordedFrame) -> io::Result<()> {
+        let mut text = String::new();
+        write_frame(&mut text, frame).map_err(|e| invalid_data(e.to_string()))?;
+        self.file.write_all(text.as_bytes())
+    }
+}
+
+/// 录制的按键状态，回放时代替实时按键
+pub(crate) struct RecordedKeys<'a>(pub(crate) &'a [hotkey::Key]);
+
+impl KeySource for RecordedKeys<'_> {
+    fn is_down(&self, key: hotkey::Key) -> bool {
+        self.0.contains(&key)
+    }
+}
+
+/// 当前按下的按键
+pub(crate) fn keys_down(source: &dyn KeySource) -> Vec<hotkey::Key> {
+    hotkey::Key::ALL
+        .iter()
+        .copied()
+        .filter(|key| source.is_down(*key))
+        .collect()
+}
+
+fn parse_mode(name: &str) -> Option<OverlayMode> {
+    Some(match name {
+        "Passive" => OverlayMode::Passive,
+        "Interactive" => OverlayMode::Interactive,
+        "Auto" => OverlayMode::Auto,
+        "Regions" => OverlayMode::Regions,
+        _ => return None,
+    })
+}
+
+fn invalid_data(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+fn parse_event(kind: &str, args: &[&str]) -> Option<InputEvent> {
+    let float = |index: usize| args.get(index)?.parse::<f32>().ok();
+    let flag = |index: usize| match *args.get(index)? {
+        "0" => Some(false),
+        "1" => Some(true),
+        _ => None,
+    };
+    let key = |index: usize| {
+        let name = *args.get(index)?;
+        Key::VARIANTS
+            .into_iter()
+            .find(|key| format!("{key:?}") == name)
+    };
+    Some(match kind {
+        "move" => InputEvent::MouseMove {
+            pos: [float(0)?, float(1)?],
+        },
+        "button" => {
+            let name = *args.first()?;
+            let button = MouseButton::VARIANTS
+                .into_iter()
+                .find(|button| format!("{button:?}") == name)?;
+            InputEvent::MouseButton {
+                button,
+                down: flag(1)?,
+            }
+        }
+        "wheel" => InputEvent::MouseWheel {
+            delta: [float(0)?, float(1)?],
+        },
+        "key" => InputEvent::Key {
+            key: key(0)?,
+            down: flag(1)?,
+        },
+        "analog" => InputEvent::KeyAnalog {
+            key: key(0)?,
+            down: flag(1)?,
+            value: float(2)?,
+        },
+        "text" => InputEvent::Text(char::from_u32(args.first()?.parse().ok()?)?),
+        "focus" => InputEvent::Focus(flag(0)?),
+        _ => return None,
+    })
+}
+
+/// 按帧回放录制的输入
+#[derive(Clone, Debug)]
+pub struct InputReplay {
+    frames: Vec<RecordedFrame>,
+    position: usize,
+}
+
+impl InputReplay {
+    /// 读取录制文件并开始回放
+    pub fn load(path: impl AsRef<Path>) -> io::Result<InputReplay> {
+        Ok(InputRecording::load(path)?.replay())
+    }
+
+    /// 下一帧，回放结束后返回None
+    pub fn next_frame(&mut self) -> Option<&RecordedFrame> {
+        let frame = self.frames.get(self.position)?;
+        self.position += 1;
+        Some(frame)
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.position >= self.frames.len()
+    }
+
+    /// 已回放的帧数
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    /// 不创建窗口与渲染器，直接驱动imgui回放所有帧，用于复现交互问题。
+    /// 只回放输入事件、帧间隔与窗口大小，录制的交互模式与热键按键需要[crate::window::Windows]才能回放
+    /// [render] 渲染函数，返回false时提前结束
+    pub fn run_headless<R>(&mut self, imgui: &mut Context, display_size: [f32; 2], mut render: R)
+    where
+        R: FnMut(&mut Ui) -> bool,
+    {
+        // 没有渲染器时也需要生成字体纹理，否则无法开始新帧
+        if !imgui.fonts().is_built() {
+            imgui.fonts().build_rgba32_texture();
+        }
+        while let Some(frame) = self.next_frame() {
+            let io = imgui.io_mut();
+            io.display_size = if frame.display_size == [0.0, 0.0] {
+                display_size
+            } else {
+                frame.display_size
+            };
+            // imgui要求帧间隔大于0
+            io.delta_time = frame.delta_time.max(f32::EPSILON);
+            for event in &frame.events {
+                event.apply(io);
+            }
+            let ui = imgui.new_frame();
+            let keep_running = render(ui);
+            imgui.render();
+            if !keep_running {
+                break;
+            }
+        }
+    }
+}
+
+/// 录制或回放输入的方式
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub enum InputCapture {
+    #[default]
+    None,
+    /// 录制输入，每帧追加到文件
+    Record(PathBuf),
+    /// 回放录制文件，回放期间忽略实时输入，结束后恢复实时输入
+    Replay(PathBuf),
+}
@@ -0,0 +1,449 @@
+use std::collections::BTreeMap;
+#[cfg(feature = "theme")]
+use std::io;
+#[cfg(feature = "theme")]
+use std::path::{Path, PathBuf};
+#[cfg(feature = "theme")]
+use std::time::{Duration, Instant, SystemTime};
+
+use imgui::{Style, StyleColor};
+#[cfg(feature = "theme")]
+use serde::{Deserialize, Serialize};
+
+use crate::fonts::{FontOptions, FontRole, FontSpec};
+
+/// 主题的基础配色，[Theme::colors]在此之上覆盖
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "theme",
+    derive(Serialize, Deserialize),
+    serde(rename_all = "lowercase")
+)]
+pub enum ThemeBase {
+    #[default]
+    Dark,
+    Light,
+    Classic,
+}
+
+/// 尺寸，未设置的项保持imgui的默认值
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "theme", derive(Serialize, Deserialize), serde(default))]
+pub struct ThemeSizes {
+    /// 全局不透明度
+    #[cfg_attr(feature = "theme", serde(skip_serializing_if = "Option::is_none"))]
+    pub alpha: Option<f32>,
+    #[cfg_attr(feature = "theme", serde(skip_serializing_if = "Option::is_none"))]
+    pub window_padding: Option<[f32; 2]>,
+    #[cfg_attr(feature = "theme", serde(skip_serializing_if = "Option::is_none"))]
+    pub frame_padding: Option<[f32; 2]>,
+    #[cfg_attr(feature = "theme", serde(skip_serializing_if = "Option::is_none"))]
+    pub item_spacing: Option<[f32; 2]>,
+    #[cfg_attr(feature = "theme", serde(skip_serializing_if = "Option::is_none"))]
+    pub item_inner_spacing: Option<[f32; 2]>,
+    #[cfg_attr(feature = "theme", serde(skip_serializing_if = "Option::is_none"))]
+    pub indent_spacing: Option<f32>,
+    #[cfg_attr(feature = "theme", serde(skip_serializing_if = "Option::is_none"))]
+    pub scrollbar_size: Option<f32>,
+    #[cfg_attr(feature = "theme", serde(skip_serializing_if = "Option::is_none"))]
+    pub grab_min_size: Option<f32>,
+    #[cfg_attr(feature = "theme", serde(skip_serializing_if = "Option::is_none"))]
+    pub window_border_size: Option<f32>,
+    #[cfg_attr(feature = "theme", serde(skip_serializing_if = "Option::is_none"))]
+    pub frame_border_size: Option<f32>,
+    #[cfg_attr(feature = "theme", serde(skip_serializing_if = "Option::is_none"))]
+    pub popup_border_size: Option<f32>,
+}
+
+/// 圆角，未设置的项保持imgui的默认值
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "theme", derive(Serialize, Deserialize), serde(default))]
+pub struct ThemeRounding {
+    #[cfg_attr(feature = "theme", serde(skip_serializing_if = "Option::is_none"))]
+    pub window: Option<f32>,
+    #[cfg_attr(feature = "theme", serde(skip_serializing_if = "Option::is_none"))]
+    pub child: Option<f32>,
+    #[cfg_attr(feature = "theme", serde(skip_serializing_if = "Option::is_none"))]
+    pub frame: Option<f32>,
+    #[cfg_attr(feature = "theme", serde(skip_serializing_if = "Option::is_none"))]
+    pub popup: Option<f32>,
+    #[cfg_attr(feature = "theme", serde(skip_serializing_if = "Option::is_none"))]
+    pub scrollbar: Option<f32>,
+    #[cfg_attr(feature = "theme", serde(skip_serializing_if = "Option::is_none"))]
+    pub grab: Option<f32>,
+    #[cfg_attr(feature = "theme", serde(skip_serializing_if = "Option::is_none"))]
+    pub tab: Option<f32>,
+}
+
+/// 字体角色的字体族与字号
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "theme", derive(Serialize, Deserialize))]
+pub struct ThemeFont {
+    /// 拉丁字体族，为空时保留当前字体只修改字号
+    #[cfg_attr(feature = "theme", serde(default))]
+    pub families: Vec<String>,
+    pub size: f32,
+}
+
+/// 各字体角色，未设置的角色保持当前字体
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "theme", derive(Serialize, Deserialize), serde(default))]
+pub struct ThemeFonts {
+    #[cfg_attr(feature = "theme", serde(skip_serializing_if = "Option::is_none"))]
+    pub body: Option<ThemeFont>,
+    #[cfg_attr(feature = "theme", serde(skip_serializing_if = "Option::is_none"))]
+    pub title: Option<ThemeFont>,
+    #[cfg_attr(feature = "theme", serde(skip_serializing_if = "Option::is_none"))]
+    pub mono: Option<ThemeFont>,
+}
+
+/// 主题，开启`theme`特性后可从TOML/JSON读取
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "theme", derive(Serialize, Deserialize), serde(default))]
+pub struct Theme {
+    pub name: String,
+    pub base: ThemeBase,
+    /// 按名称覆盖的颜色，名称与[StyleColor]的变体相同，例如`WindowBg`，不区分大小写
+    pub colors: BTreeMap<String, [f32; 4]>,
+    pub sizes: ThemeSizes,
+    pub rounding: ThemeRounding,
+    pub fonts: ThemeFonts,
+}
+
+impl Theme {
+    fn bundled(name: &str, base: ThemeBase) -> Theme {
+        Theme {
+            name: name.to_owned(),
+            base,
+            rounding: ThemeRounding {
+                window: Some(12.0),
+                ..ThemeRounding::default()
+            },
+            ..Theme::default()
+        }
+    }
+
+    pub fn dark() -> Theme {
+        Theme::bundled("dark", ThemeBase::Dark)
+    }
+
+    pub fn light() -> Theme {
+        Theme::bundled("light", ThemeBase::Light)
+    }
+
+    pub fn classic() -> Theme {
+        Theme::bundled("classic", ThemeBase::Classic)
+    }
+
+    /// 半透明深色背景，适合叠加在游戏画面上
+    pub fn translucent() -> Theme {
+        let mut theme = Theme::bundled("translucent", ThemeBase::Dark);
+        for (name, color) in [
+            ("WindowBg", [0.06, 0.06, 0.08, 0.78]),
+            ("ChildBg", [0.0, 0.0, 0.0, 0.0]),
+            ("PopupBg", [0.08, 0.08, 0.10, 0.92]),
+            ("TitleBg", [0.06, 0.06, 0.08, 0.78]),
+            ("TitleBgActive", [0.12, 0.14, 0.20, 0.90]),
+            ("Border", [1.0, 1.0, 1.0, 0.12]),
+        ] {
+            theme.colors.insert(name.to_owned(), color);
+        }
+        theme.rounding.frame = Some(4.0);
+        theme.rounding.grab = Some(4.0);
+        theme.sizes.window_border_size = Some(1.0);
+        theme
+    }
+
+    /// 内置主题
+    pub fn all_bundled() -> Vec<Theme> {
+        vec![
+            Theme::dark(),
+            Theme::light(),
+            Theme::classic(),
+            Theme::translucent(),
+        ]
+    }
+
+    /// 按名称查找内置主题
+    pub fn find_bundled(name: &str) -> Option<Theme> {
+        Theme::all_bundled()
+            .into_iter()
+            .find(|theme| theme.name.eq_ignore_ascii_case(name))
+    }
+
+    /// 名称对应的颜色，不区分大小写
+    pub fn style_color(name: &str) -> Option<StyleColor> {
+        StyleColor::VARIANTS
+            .into_iter()
+            .find(|color| format!("{color:?}").eq_ignore_ascii_case(name))
+    }
+
+    /// 设置颜色
+    pub fn set_color(&mut self, color: StyleColor, value: [f32; 4]) {
+        self.colors.insert(format!("{color:?}"), value);
+    }
+
+    /// 应用到未缩放的样式，颜色名称无效时忽略该项
+    pub fn apply(&self, style: &mut Style) {
+        match self.base {
+            ThemeBase::Dark => style.use_dark_colors(),
+            ThemeBase::Light => style.use_light_colors(),
+            ThemeBase::Classic => style.use_classic_colors(),
+        };
+        for (name, value) in &self.colors {
+            match Theme::style_color(name) {
+                Some(color) => style[color] = *value,
+                None => log::warn!("Unknown style color {name:?} in theme {:?}", self.name),
+            }
+        }
+        let sizes = &self.sizes;
+        set(&mut style.alpha, sizes.alpha);
+        set(&mut style.window_padding, sizes.window_padding);
+        set(&mut style.frame_padding, sizes.frame_padding);
+        set(&mut style.item_spacing, sizes.item_spacing);
+        set(&mut style.item_inner_spacing, sizes.item_inner_spacing);
+        set(&mut style.indent_spacing, sizes.indent_spacing);
+        set(&mut style.scrollbar_size, sizes.scrollbar_size);
+        set(&mut style.grab_min_size, sizes.grab_min_size);
+        set(&mut style.window_border_size, sizes.window_border_size);
+        set(&mut style.frame_border_size, sizes.frame_border_size);
+        set(&mut style.popup_border_size, sizes.popup_border_size);
+        let rounding = &self.rounding;
+        set(&mut style.window_rounding, rounding.window);
+        set(&mut style.child_rounding, rounding.child);
+        set(&mut style.frame_rounding, rounding.frame);
+        set(&mut style.popup_rounding, rounding.popup);
+        set(&mut style.scrollbar_rounding, rounding.scrollbar);
+        set(&mut style.grab_rounding, rounding.grab);
+        set(&mut style.tab_rounding, rounding.tab);
+    }
+
+    /// 应用字体角色，返回字体配置是否变化
+    pub fn apply_fonts(&self, options: &mut FontOptions) -> bool {
+        let before = options.clone();
+        for (role, font) in [
+            (FontRole::Body, &self.fonts.body),
+            (FontRole::Title, &self.fonts.title),
+            (FontRole::Mono, &self.fonts.mono),
+        ] {
+            let Some(font) = font else {
+                continue;
+            };
+            let spec = options.spec_mut(role);
+            if font.families.is_empty() {
+                spec.size = font.size;
+            } else {
+                let families: Vec<&str> = font.families.iter().map(String::as_str).collect();
+                *spec = FontSpec::with_fallbacks(&families, font.size);
+            }
+        }
+        *options != before
+    }
+}
+
+fn set<T>(target: &mut T, value: Option<T>) {
+    if let Some(value) = value {
+        *target = value;
+    }
+}
+
+#[cfg(feature = "theme")]
+fn invalid_data(error: impl std::fmt::Display) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, error.to_string())
+}
+
+#[cfg(feature = "theme")]
+impl Theme {
+    pub fn from_toml(text: &str) -> io::Result<Theme> {
+        toml::from_str(text).map_err(invalid_data)
+    }
+
+    pub fn to_toml(&self) -> io::Result<String> {
+        toml::to_string(self).map_err(invalid_data)
+    }
+
+    pub fn from_json(text: &str) -> io::Result<Theme> {
+        serde_json::from_str(text).map_err(invalid_data)
+    }
+
+    pub fn to_json(&self) -> io::Result<String> {
+        serde_json::to_string_pretty(self).map_err(invalid_data)
+    }
+
+    /// 按扩展名读取，`.json`为JSON，其它为TOML。未设置名称时使用文件名
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Theme> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path)?;
+        let mut theme = if is_json(path) {
+            Theme::from_json(&text)?
+        } else {
+            Theme::from_toml(&text)?
+        };
+        if theme.name.is_empty() {
+            if let Some(stem) = path.file_stem() {
+                theme.name = stem.to_string_lossy().into_owned();
+            }
+        }
+        Ok(theme)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let path = path.as_ref();
+        let text = if is_json(path) {
+            self.to_json()?
+        } else {
+            self.to_toml()?
+        };
+        std::fs::write(path, text)
+    }
+}
+
+#[cfg(feature = "theme")]
+fn is_json(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("json"))
+}
+
+/// 检查主题文件的修改时间，变化时重新读取
+#[cfg(feature = "theme")]
+pub(crate) struct ThemeWatcher {
+    path: PathBuf,
+    modified: Option<SystemTime>,
+    last_poll: Instant,
+}
+
+#[cfg(feature = "theme")]
+impl ThemeWatcher {
+    const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+    pub(crate) fn new(path: PathBuf) -> ThemeWatcher {
+        ThemeWatcher {
+            modified: modified(&path),
+            path,
+            last_poll: Instant::now(),
+        }
+    }
+
+    /// 读取当前的主题文件
+    pub(crate) fn load(&self) -> Option<Theme> {
+        match Theme::load(&self.path) {
+            Ok(theme) => Some(theme),
+            Err(e) => {
+                log::warn!("Failed to load theme {}: {e}", self.path.display());
+                None
+            }
+        }
+    }
+
+    /// 文件变化后返回新的主题，文件内容无效时保留当前主题
+    pub(crate) fn poll(&mut self) -> Option<Theme> {
+        if self.last_poll.elapsed() < ThemeWatcher::POLL_INTERVAL {
+            return None;
+        }
+        self.last_poll = Instant::now();
+        let modified = modified(&self.path);
+        if modified == self.modified {
+            return None;
+        }
+        self.modified = modified;
+        log::debug!("Reloading theme {}", self.path.display());
+        self.load()
+    }
+}
+
+#[cfg(feature = "theme")]
+fn modified(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn default_style() -> Style {
+        unsafe {
+            let raw = imgui::sys::ImGuiStyle_ImGuiStyle();
+            let style = *(raw as *const Style);
+            imgui::sys::ImGuiStyle_destroy(raw);
+            style
+        }
+    }
+
+    fn sample() -> Theme {
+        let mut theme = Theme::bundled("sample", ThemeBase::Light);
+        theme.set_color(StyleColor::WindowBg, [0.1, 0.2, 0.3, 0.5]);
+        theme.sizes.alpha = Some(0.9);
+        theme.sizes.window_padding = Some([4.0, 6.0]);
+        theme.rounding.frame = Some(3.0);
+        theme.fonts.body = Some(ThemeFont {
+            families: vec!["Segoe UI".to_owned()],
+            size: 16.0,
+        });
+        theme.fonts.mono = Some(ThemeFont {
+            families: Vec::new(),
+            size: 14.0,
+        });
+        theme
+    }
+
+    #[test]
+    fn color_names_ignore_case() {
+        assert_eq!(Theme::style_color("windowbg"), Some(StyleColor::WindowBg));
+        assert_eq!(Theme::style_color("WINDOWBG"), Some(StyleColor::WindowBg));
+        assert_eq!(Theme::style_color("WindowBackground"), None);
+    }
+
+    #[test]
+    fn apply_skips_unknown_colors() {
+        let mut theme = Theme::dark();
+        theme
+            .colors
+            .insert("windowbg".to_owned(), [1.0, 0.0, 0.0, 1.0]);
+        theme.colors.insert("Nope".to_owned(), [0.0, 1.0, 0.0, 1.0]);
+        theme.rounding.frame = Some(5.0);
+        let mut style = default_style();
+        theme.apply(&mut style);
+        assert_eq!(style[StyleColor::WindowBg], [1.0, 0.0, 0.0, 1.0]);
+        assert_eq!(style.frame_rounding, 5.0);
+        assert_eq!(style.window_rounding, 12.0);
+
+        let mut expected = default_style();
+        expected.use_dark_colors();
+        assert_eq!(style[StyleColor::Text], expected[StyleColor::Text]);
+    }
+
+    #[cfg(feature = "theme")]
+    #[test]
+    fn toml_round_trip() {
+        let theme = sample();
+        let text = theme.to_toml().unwrap();
+        assert_eq!(Theme::from_toml(&text).unwrap(), theme);
+    }
+
+    #[cfg(feature = "theme")]
+    #[test]
+    fn json_round_trip() {
+        let theme = sample();
+        let text = theme.to_json().unwrap();
+        assert_eq!(Theme::from_json(&text).unwrap(), theme);
+    }
+
+    #[cfg(feature = "theme")]
+    #[test]
+    fn missing_fields_use_defaults() {
+        let theme =
+            Theme::from_toml("name = \"partial\"\n[colors]\nText = [1, 1, 1, 1]\n").unwrap();
+        assert_eq!(theme.base, ThemeBase::Dark);
+        assert_eq!(theme.colors["Text"], [1.0; 4]);
+        assert_eq!(theme.sizes, ThemeSizes::default());
+    }
+
+    #[cfg(feature = "theme")]
+    #[test]
+    fn invalid_input_is_invalid_data() {
+        let error = Theme::from_toml("base = \"neon\"").unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        let error = Theme::from_json("{").unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    }
+}
@@ -0,0 +1,192 @@
+use std::cell::RefCell;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc;
+use std::thread::JoinHandle;
+
+use windows::Win32::Foundation::{HWND, LPARAM, WPARAM};
+use windows::Win32::System::Threading::GetCurrentThreadId;
+use windows::Win32::UI::Accessibility::{SetWinEventHook, UnhookWinEvent, HWINEVENTHOOK};
+use windows::Win32::UI::WindowsAndMessaging::{
+    DispatchMessageW, GetMessageW, GetWindowThreadProcessId, PeekMessageW, PostThreadMessageW,
+    TranslateMessage, CHILDID_SELF, EVENT_OBJECT_LOCATIONCHANGE, EVENT_SYSTEM_MOVESIZEEND,
+    EVENT_SYSTEM_MOVESIZESTART, MSG, OBJID_WINDOW, PM_NOREMOVE, WINEVENT_OUTOFCONTEXT, WM_QUIT,
+};
+
+use crate::window_tracker::WindowTracker;
+
+/// 目标窗口正在被拖动或调整大小
+static DRAGGING: AtomicBool = AtomicBool::new(false);
+
+/// 跟踪目标窗口的方式
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TrackingMode {
+    /// 每帧渲染前跟踪一次(默认)
+    #[default]
+    PerFrame,
+    /// 目标窗口移动时立即跟随，不依赖渲染循环
+    EventDriven,
+}
+
+/// 跟踪选项
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct TrackingOptions {
+    pub mode: TrackingMode,
+    /// 拖动目标窗口时隐藏覆盖内容
+    pub hide_while_dragging: bool,
+    /// 按上一帧的移动速度预测位置，单位为帧，0表示不预测，仅对[TrackingMode::PerFrame]生效
+    pub prediction: f32,
+}
+
+impl TrackingOptions {
+    /// 是否需要监听目标窗口事件
+    pub(crate) fn needs_hook(&self) -> bool {
+        self.mode == TrackingMode::EventDriven || self.hide_while_dragging
+    }
+}
+
+/// 目标窗口是否正在被拖动
+pub(crate) fn is_dragging() -> bool {
+    DRAGGING.load(Ordering::Acquire)
+}
+
+struct HookState {
+    tracker: WindowTracker,
+    overlay: HWND,
+    follow: bool,
+}
+
+/// 将跟踪器移交给监听线程
+struct SendState(HookState);
+
+unsafe impl Send for SendState {}
+
+impl SendState {
+    fn into_inner(self) -> HookState {
+        self.0
+    }
+}
+
+thread_local! {
+    static HOOK_STATE: RefCell<Option<HookState>> = const { RefCell::new(None) };
+}
+
+/// 在独立线程上监听目标窗口的移动事件
+pub(crate) struct TrackingHook {
+    thread_id: u32,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl TrackingHook {
+    /// 启动监听线程，`follow`为true时在目标移动时立即移动覆盖窗口
+    pub(crate) fn start(
+        tracker: &WindowTracker,
+        overlay: HWND,
+        follow: bool,
+    ) -> Option<TrackingHook> {
+        let mut process_id = 0;
+        let thread_id = unsafe { GetWindowThreadProcessId(tracker.hwnd, Some(&mut process_id)) };
+        if thread_id == 0 {
+            log::warn!("Tracked window has no owning thread, event tracking disabled");
+            return None;
+        }
+        let state = SendState(HookState {
+            tracker: tracker.clone(),
+            overlay,
+            follow,
+        });
+        let (sender, receiver) = mpsc::channel();
+        let handle = std::thread::spawn(move || {
+            let state = state.into_inner();
+            unsafe {
+                // 创建消息队列，之后才能接收WM_QUIT
+                let mut msg = MSG::default();
+                let _ = PeekMessageW(&mut msg, None, 0, 0, PM_NOREMOVE);
+                let hooks = [
+                    SetWinEventHook(
+                        EVENT_SYSTEM_MOVESIZESTART,
+                        EVENT_SYSTEM_MOVESIZEEND,
+                        None,
+                        Some(win_event),
+                        process_id,
+                        thread_id,
+                        WINEVENT_OUTOFCONTEXT,
+                    ),
+                    SetWinEventHook(
+                        EVENT_OBJECT_LOCATIONCHANGE,
+                        EVENT_OBJECT_LOCATIONCHANGE,
+                        None,
+                        Some(win_event),
+                        process_id,
+                        thread_id,
+                        WINEVENT_OUTOFCONTEXT,
+                    ),
+                ];
+                HOOK_STATE.with(|cell| *cell.borrow_mut() = Some(state));
+                let _ = sender.send(GetCurrentThreadId());
+                while GetMessageW(&mut msg, None, 0, 0).as_bool() {
+                    let _ = TranslateMessage(&msg);
+                    let _ = DispatchMessageW(&msg);
+                }
+                for hook in hooks {
+                    if !hook.is_invalid() {
+                        let _ = UnhookWinEvent(hook);
+                    }
+                }
+                DRAGGING.store(false, Ordering::Release);
+            }
+        });
+        match receiver.recv() {
+            Ok(thread_id) => Some(TrackingHook {
+                thread_id,
+                handle: Some(handle),
+            }),
+            Err(_) => {
+                log::warn!("Tracking thread exited before installing hooks");
+                None
+            }
+        }
+    }
+}
+
+impl Drop for TrackingHook {
+    fn drop(&mut self) {
+        unsafe {
+            let _ = PostThreadMessageW(self.thread_id, WM_QUIT, WPARAM(0), LPARAM(0));
+        }
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+unsafe extern "system" fn win_event(
+    _hook: HWINEVENTHOOK,
+    event: u32,
+    hwnd: HWND,
+    id_object: i32,
+    id_child: i32,
+    _event_thread: u32,
+    _event_time: u32,
+) {
+    if id_object != OBJID_WINDOW.0 || id_child != CHILDID_SELF as i32 {
+        return;
+    }
+    HOOK_STATE.with(|cell| {
+        let mut state = cell.borrow_mut();
+        let state = match state.as_mut() {
+            Some(state) if state.tracker.hwnd == hwnd => state,
+            _ => return,
+        };
+        match event {
+            EVENT_SYSTEM_MOVESIZESTART => DRAGGING.store(true, Ordering::Release),
+            EVENT_SYSTEM_MOVESIZEEND => {
+                DRAGGING.store(false, Ordering::Release);
+                if state.follow {
+                    state.tracker.follow(state.overlay);
+                }
+            }
+            EVENT_OBJECT_LOCATIONCHANGE if state.follow => state.tracker.follow(state.overlay),
+            _ => {}
+        }
+    });
+}
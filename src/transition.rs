@@ -0,0 +1,285 @@
+use std::time::Duration;
+
+use imgui::Style;
+
+/// 缓动曲线
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Easing {
+    Linear,
+    /// 三次方加速
+    EaseIn,
+    /// 三次方减速
+    EaseOut,
+    /// 先加速后减速
+    #[default]
+    EaseInOut,
+}
+
+impl Easing {
+    /// 将0到1的进度映射为插值系数
+    pub fn apply(self, t: f32) -> f32 {
+        let t = t.clamp(0.0, 1.0);
+        match self {
+            Easing::Linear => t,
+            Easing::EaseIn => t * t * t,
+            Easing::EaseOut => 1.0 - (1.0 - t).powi(3),
+            Easing::EaseInOut => {
+                if t < 0.5 {
+                    4.0 * t * t * t
+                } else {
+                    1.0 - (-2.0 * t + 2.0).powi(3) / 2.0
+                }
+            }
+        }
+    }
+}
+
+/// 过渡动画的时长与缓动曲线
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Transition {
+    pub duration: Duration,
+    pub easing: Easing,
+}
+
+impl Default for Transition {
+    fn default() -> Transition {
+        Transition::new(Duration::from_millis(250), Easing::EaseInOut)
+    }
+}
+
+impl Transition {
+    /// 立即切换
+    pub const NONE: Transition = Transition {
+        duration: Duration::ZERO,
+        easing: Easing::Linear,
+    };
+
+    pub const fn new(duration: Duration, easing: Easing) -> Transition {
+        Transition { duration, easing }
+    }
+
+    pub fn is_instant(&self) -> bool {
+        self.duration.is_zero()
+    }
+
+    /// 经过[elapsed]秒后的进度，0到1
+    fn progress(&self, elapsed: f32) -> f32 {
+        if self.is_instant() {
+            1.0
+        } else {
+            (elapsed / self.duration.as_secs_f32()).clamp(0.0, 1.0)
+        }
+    }
+}
+
+fn lerp(from: f32, to: f32, t: f32) -> f32 {
+    from + (to - from) * t
+}
+
+/// 在两个样式间插值颜色、透明度与圆角，其余字段取[to]的值
+pub fn lerp_style(from: &Style, to: &Style, t: f32) -> Style {
+    let mut style = *to;
+    for (color, (from, to)) in style
+        .colors
+        .iter_mut()
+        .zip(from.colors.iter().zip(to.colors.iter()))
+    {
+        for i in 0..4 {
+            color[i] = lerp(from[i], to[i], t);
+        }
+    }
+    style.alpha = lerp(from.alpha, to.alpha, t);
+    style.disabled_alpha = lerp(from.disabled_alpha, to.disabled_alpha, t);
+    style.window_rounding = lerp(from.window_rounding, to.window_rounding, t);
+    style.child_rounding = lerp(from.child_rounding, to.child_rounding, t);
+    style.popup_rounding = lerp(from.popup_rounding, to.popup_rounding, t);
+    style.frame_rounding = lerp(from.frame_rounding, to.frame_rounding, t);
+    style.scrollbar_rounding = lerp(from.scrollbar_rounding, to.scrollbar_rounding, t);
+    style.grab_rounding = lerp(from.grab_rounding, to.grab_rounding, t);
+    style.tab_rounding = lerp(from.tab_rounding, to.tab_rounding, t);
+    style
+}
+
+/// 进行中的样式过渡
+pub(crate) struct StyleAnimation {
+    from: Style,
+    to: Style,
+    transition: Transition,
+    elapsed: f32,
+}
+
+impl StyleAnimation {
+    pub(crate) fn new(from: Style, to: Style, transition: Transition) -> StyleAnimation {
+        StyleAnimation {
+            from,
+            to,
+            transition,
+            elapsed: 0.0,
+        }
+    }
+
+    pub(crate) fn target(&self) -> &Style {
+        &self.to
+    }
+
+    /// 替换目标，进度不变
+    pub(crate) fn set_target(&mut self, to: Style) {
+        self.to = to;
+    }
+
+    pub(crate) fn is_finished(&self) -> bool {
+        self.transition.progress(self.elapsed) >= 1.0
+    }
+
+    /// 推进[delta_time]秒，返回当前的样式
+    pub(crate) fn advance(&mut self, delta_time: f32) -> Style {
+        self.elapsed += delta_time;
+        let t = self.transition.progress(self.elapsed);
+        lerp_style(&self.from, &self.to, self.transition.easing.apply(t))
+    }
+}
+
+/// 显示与隐藏时的全局透明度
+pub(crate) struct Fade {
+    visible: bool,
+    /// 线性进度，0为完全隐藏，1为完全显示
+    progress: f32,
+    transition: Transition,
+}
+
+impl Fade {
+    pub(crate) fn new(transition: Transition) -> Fade {
+        Fade {
+            visible: true,
+            progress: 1.0,
+            transition,
+        }
+    }
+
+    pub(crate) fn is_visible(&self) -> bool {
+        self.visible
+    }
+
+    /// 隐藏且已完全淡出
+    pub(crate) fn is_hidden(&self) -> bool {
+        !self.visible && self.progress <= 0.0
+    }
+
+    pub(crate) fn set_visible(&mut self, visible: bool) {
+        self.visible = visible;
+    }
+
+    pub(crate) fn set_transition(&mut self, transition: Transition) {
+        self.transition = transition;
+    }
+
+    pub(crate) fn advance(&mut self, delta_time: f32) {
+        let step = if self.transition.is_instant() {
+            1.0
+        } else {
+            delta_time / self.transition.duration.as_secs_f32()
+        };
+        self.progress = if self.visible {
+            (self.progress + step).min(1.0)
+        } else {
+            (self.progress - step).max(0.0)
+        };
+    }
+
+    /// 乘到样式透明度上的系数
+    pub(crate) fn alpha(&self) -> f32 {
+        self.transition.easing.apply(self.progress)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use imgui::StyleColor;
+
+    use super::*;
+
+    const EASINGS: [Easing; 4] = [
+        Easing::Linear,
+        Easing::EaseIn,
+        Easing::EaseOut,
+        Easing::EaseInOut,
+    ];
+
+    fn default_style() -> Style {
+        unsafe {
+            let raw = imgui::sys::ImGuiStyle_ImGuiStyle();
+            let style = *(raw as *const Style);
+            imgui::sys::ImGuiStyle_destroy(raw);
+            style
+        }
+    }
+
+    #[test]
+    fn easing_endpoints() {
+        for easing in EASINGS {
+            assert_eq!(easing.apply(0.0), 0.0, "{easing:?}");
+            assert_eq!(easing.apply(1.0), 1.0, "{easing:?}");
+            assert_eq!(easing.apply(-1.0), 0.0, "{easing:?}");
+            assert_eq!(easing.apply(2.0), 1.0, "{easing:?}");
+        }
+        assert_eq!(Easing::EaseInOut.apply(0.5), 0.5);
+        assert!(Easing::EaseIn.apply(0.25) < 0.25);
+        assert!(Easing::EaseOut.apply(0.25) > 0.25);
+    }
+
+    #[test]
+    fn lerp_style_interpolates() {
+        let mut from = default_style();
+        from.use_dark_colors();
+        from[StyleColor::WindowBg] = [0.0, 0.0, 0.0, 1.0];
+        from.window_rounding = 0.0;
+        from.window_padding = [2.0, 2.0];
+        let mut to = default_style();
+        to.use_light_colors();
+        to[StyleColor::WindowBg] = [1.0, 0.5, 0.0, 1.0];
+        to.window_rounding = 10.0;
+        to.window_padding = [8.0, 8.0];
+
+        let start = lerp_style(&from, &to, 0.0);
+        assert_eq!(start[StyleColor::Text], from[StyleColor::Text]);
+        assert_eq!(start.window_rounding, 0.0);
+        let end = lerp_style(&from, &to, 1.0);
+        assert_eq!(end[StyleColor::Text], to[StyleColor::Text]);
+        assert_eq!(end.window_rounding, 10.0);
+
+        let middle = lerp_style(&from, &to, 0.5);
+        assert_eq!(middle[StyleColor::WindowBg], [0.5, 0.25, 0.0, 1.0]);
+        assert_eq!(middle.window_rounding, 5.0);
+        assert_eq!(middle.window_padding, [8.0, 8.0]);
+    }
+
+    #[test]
+    fn animation_reaches_target() {
+        let from = default_style();
+        let mut to = default_style();
+        to.alpha = 0.5;
+        let transition = Transition::new(Duration::from_millis(100), Easing::Linear);
+        let mut animation = StyleAnimation::new(from, to, transition);
+        assert!((animation.advance(0.05).alpha - 0.75).abs() < 1e-6);
+        assert!(!animation.is_finished());
+        to.alpha = 0.0;
+        animation.set_target(to);
+        assert_eq!(animation.advance(0.05).alpha, 0.0);
+        assert!(animation.is_finished());
+    }
+
+    #[test]
+    fn fade_out_and_in() {
+        let mut fade = Fade::new(Transition::new(Duration::from_millis(100), Easing::Linear));
+        fade.set_visible(false);
+        fade.advance(0.05);
+        assert!((fade.alpha() - 0.5).abs() < 1e-6);
+        assert!(!fade.is_hidden());
+        fade.advance(0.05);
+        assert!(fade.is_hidden());
+        fade.set_visible(true);
+        fade.set_transition(Transition::NONE);
+        fade.advance(0.0);
+        assert_eq!(fade.alpha(), 1.0);
+    }
+}
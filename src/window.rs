@@ -1,10 +1,39 @@
-use std::fs;
+use std::io;
 use std::os::raw::c_void;
+use std::path::PathBuf;
+use std::rc::Rc;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Mutex;
 
+use crate::canvas::{CanvasMapping, VirtualCanvas};
+use crate::clipboard::Win32Clipboard;
+use crate::cursor;
 use crate::d3d11::D3d11Render;
-use crate::window_tracker::{OverlayTarget, WindowTracker};
-use imgui::{ConfigFlags, Context, DrawData, FontConfig, FontGlyphRanges, FontSource, Style, Ui};
+use crate::font_cache::FontAtlasCache;
+use crate::fonts::{FontManager, FontOptions, FontRoles};
+use crate::frame::FrameContext;
+use crate::gamepad::{self, Gamepad, GamepadOptions, GamepadSource};
+use crate::glyphs::DynamicGlyphs;
+use crate::hit_test::HitRegions;
+use crate::hotkey::{Chord, Hotkeys, Win32KeySource};
+use crate::ime::{self, ImeBackend, ImeRequest, Win32Ime};
+use crate::input::{self, InputEvent, InputPipeline};
+use crate::layout::{Layout, LayoutOptions};
+use crate::message::{self, MessageHookId, MessageStage, WindowMessage};
+use crate::monitor::DISPLAY_CHANGED;
+use crate::record::{
+    self, InputCapture, InputReplay, RecordedFrame, RecordedKeys, RecordingWriter,
+};
+use crate::theme::Theme;
+#[cfg(feature = "theme")]
+use crate::theme::ThemeWatcher;
+use crate::tracking::{is_dragging, TrackingHook, TrackingMode, TrackingOptions};
+use crate::transition::{Fade, StyleAnimation, Transition};
+use crate::window_tracker::{Margins, OverlayAnchor, OverlayTarget, WindowTracker};
+use imgui::{
+    BackendFlags, ClipboardBackend, ConfigFlags, Context, DrawData, FontSource, MouseButton, Style,
+    Ui,
+};
 use lazy_static::lazy_static;
 use windows::Win32::Foundation::{
     COLORREF, HINSTANCE, HMODULE, LPARAM, LRESULT, POINT, TRUE, WPARAM,
@@ -13,16 +42,24 @@ use windows::Win32::Graphics::Direct3D11::{ID3D11Device, ID3D11DeviceContext};
 use windows::Win32::Graphics::Dxgi::Common::DXGI_FORMAT_B8G8R8A8_UNORM;
 use windows::Win32::Graphics::Dxgi::{DXGI_PRESENT, DXGI_SWAP_CHAIN_FLAG};
 use windows::Win32::Graphics::Gdi::{
-    CreateSolidBrush, MonitorFromPoint, ScreenToClient, UpdateWindow, ValidateRect,
+    CreateSolidBrush, MonitorFromPoint, MonitorFromRect, ScreenToClient, UpdateWindow,
+    ValidateRect, HMONITOR, MONITOR_DEFAULTTONEAREST, MONITOR_DEFAULTTOPRIMARY,
 };
 use windows::Win32::System::LibraryLoader::GetModuleHandleA;
+use windows::Win32::System::RemoteDesktop::{
+    WTSRegisterSessionNotification, WTSUnRegisterSessionNotification, NOTIFY_FOR_THIS_SESSION,
+};
 use windows::Win32::UI::Input::KeyboardAndMouse::SetActiveWindow;
+use windows::Win32::UI::Shell::{DragAcceptFiles, DragFinish, HDROP};
 use windows::{core::*, Win32::Foundation::HWND, Win32::UI::WindowsAndMessaging::*};
 
 lazy_static! {
     static ref GLOBAL_DATA: Mutex<Option<D3d11Render>> = Mutex::new(None);
 }
 
+/// 覆盖窗口收到`WM_DPICHANGED`
+static DPI_CHANGED: AtomicBool = AtomicBool::new(false);
+
 #[macro_export]
 macro_rules! loword {
     ($uint:expr) => {
@@ -62,6 +99,7 @@ extern "C" {
     fn ImGui_ImplDX11_RenderDrawData(draw_data: *const DrawData);
 
     fn ImGui_ImplDX11_Shutdown();
+    fn ImGui_ImplDX11_InvalidateDeviceObjects();
     fn ImGui_ImplWin32_Shutdown();
     fn ImGui_ImplWin32_EnableDpiAwareness();
     /// 隐藏边框
@@ -78,50 +116,126 @@ impl FrameRate {
     pub const UN_LIMITED: FrameRate = FrameRate(0);
 }
 
+/// 覆盖窗口的交互模式
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OverlayMode {
+    /// 始终鼠标穿透，不获取键盘焦点
+    Passive,
+    /// 捕获鼠标与键盘
+    Interactive,
+    /// 鼠标悬停在imgui窗口上时捕获鼠标(默认)
+    #[default]
+    Auto,
+    /// 鼠标位于可交互区域时捕获鼠标，区域由[FrameContext::mark_interactive]与[HitRegions]指定。
+    /// 每帧检查一次光标，进出区域后最多晚一帧切换穿透
+    Regions,
+}
+
+impl OverlayMode {
+    /// 在交互模式与`base`之间切换
+    pub(crate) fn toggled(self, base: OverlayMode) -> OverlayMode {
+        match (self, base) {
+            (OverlayMode::Interactive, OverlayMode::Interactive) => OverlayMode::Passive,
+            (OverlayMode::Interactive, base) => base,
+            _ => OverlayMode::Interactive,
+        }
+    }
+}
+
+/// 切换交互模式的热键动作名称
+pub const MODE_TOGGLE_ACTION: &str = "overlay.toggle_mode";
+
 pub struct WindowsOptions {
     /// imgui绘制窗口
     pub title: String,
     /// 需要覆盖的目标窗口
     pub overlay_target: OverlayTarget,
+    /// 覆盖目标窗口的哪一块区域
+    pub anchor: OverlayAnchor,
+    /// 锚定区域的内边距
+    pub margins: Margins,
+    /// 跟踪目标窗口的方式
+    pub tracking: TrackingOptions,
+    /// 虚拟画布，设置后可通过[FrameContext::canvas]映射HUD坐标
+    pub virtual_canvas: Option<VirtualCanvas>,
+    /// 交互模式
+    pub mode: OverlayMode,
+    /// 切换交互模式的热键，注册为[MODE_TOGGLE_ACTION]
+    pub mode_toggle: Option<Chord>,
+    /// 接受拖放的文件，通过[WindowEvent::FilesDropped](crate::WindowEvent::FilesDropped)通知消息钩子
+    pub accept_dropped_files: bool,
+    /// 由imgui在覆盖层中绘制光标并隐藏系统光标，用于会隐藏系统光标的目标
+    pub software_cursor: bool,
+    /// 交互模式下的背景遮罩颜色
+    pub dim_background: Option<[f32; 4]>,
+    /// 热键，可在渲染函数中通过[FrameContext::hotkeys]按名称查询
+    pub hotkeys: Hotkeys,
+    /// 手柄导航
+    pub gamepad: GamepadOptions,
+    /// 录制或回放输入
+    pub input_capture: InputCapture,
     /// 帧率
     pub frame_rate: FrameRate,
     pub dll_hinstance: usize,
-    /// 初始化样式
+    /// 主题
+    pub theme: Option<Theme>,
+    /// 主题文件，优先于[WindowsOptions::theme]，文件变化时重新应用
+    #[cfg(feature = "theme")]
+    pub theme_file: Option<PathBuf>,
+    /// 按覆盖目标保存窗口位置与大小，默认为None不保存。
+    /// 例如`Some(LayoutOptions::new(FileLayoutStorage::user_config("app")?))`
+    pub layout: Option<LayoutOptions>,
+    /// 切换主题时的过渡动画，[Windows::set_style]不做过渡
+    pub style_transition: Transition,
+    /// 显示与隐藏时的淡入淡出
+    pub fade: Transition,
+    /// 初始化样式，在主题之后调用，只在启动时调用一次。
+    /// 字体图集重建时会丢弃这里加入的字体，字体改用[WindowsOptions::fonts]或[WindowsOptions::font_init]
     pub style_init: Option<Box<dyn Fn(&mut Context) -> ()>>,
+    /// 修改主题应用后的样式，尺寸为DPI缩放前的值。
+    /// 启动时在[WindowsOptions::style_init]之前调用，运行时切换主题后再次调用
+    pub theme_style: Option<Rc<dyn Fn(&mut Style)>>,
+    /// 字体角色及其回退链，通过[FrameContext::font]使用
+    pub fonts: FontOptions,
+    /// 自定义字体初始化，设置后不再使用[WindowsOptions::fonts]，所有角色使用第一个字体。
+    /// 第二个参数为DPI缩放比例，DPI变化时会清空字体并重新调用
+    pub font_init: Option<Rc<dyn Fn(&mut Context, f32)>>,
+    /// [crate::GlyphRanges::Dynamic]按需加载的字符缓存文件，下次启动时预先加入字体图集
+    pub glyph_cache: Option<PathBuf>,
+    /// 字体图集缓存目录，启动与重建字体时载入已光栅化的图集
+    pub font_cache: Option<PathBuf>,
 }
 
 impl Default for WindowsOptions {
     fn default() -> WindowsOptions {
-        let result = fs::read(r"C:\Windows\Fonts\simhei.ttf");
-        let style_init: Option<Box<dyn Fn(&mut Context) -> ()>> = if result.is_err() {
-            log::warn!("simhei read fail");
-            None
-        } else {
-            let vec = result.unwrap();
-            Some(Box::new(move |imgui| {
-                // 设置主题
-                imgui.style_mut().use_classic_colors();
-                // 设置圆角
-                imgui.style_mut().window_rounding = 12.0;
-                // 设置字体
-                imgui.fonts().add_font(&[FontSource::TtfData {
-                    data: &*vec,
-                    size_pixels: 12.0,
-                    // config:None
-                    config: Some(FontConfig {
-                        glyph_ranges: FontGlyphRanges::chinese_simplified_common(),
-                        rasterizer_multiply: 2f32,
-                        oversample_h: 4,
-                        ..FontConfig::default()
-                    }),
-                }]);
-            }))
-        };
         WindowsOptions {
             title: String::from("lingex_imgui_overlay"),
-            overlay_target: OverlayTarget::Window(unsafe { GetDesktopWindow() }),
+            overlay_target: OverlayTarget::PrimaryMonitor,
+            anchor: OverlayAnchor::default(),
+            margins: Margins::default(),
+            tracking: TrackingOptions::default(),
+            virtual_canvas: None,
+            mode: OverlayMode::default(),
+            mode_toggle: None,
+            accept_dropped_files: false,
+            software_cursor: false,
+            dim_background: None,
+            hotkeys: Hotkeys::default(),
+            gamepad: GamepadOptions::default(),
+            input_capture: InputCapture::None,
             frame_rate: FrameRate(1),
-            style_init,
+            theme: Some(Theme::classic()),
+            #[cfg(feature = "theme")]
+            theme_file: None,
+            layout: None,
+            style_transition: Transition::default(),
+            fade: Transition::default(),
+            style_init: None,
+            theme_style: None,
+            fonts: FontOptions::default(),
+            font_init: None,
+            glyph_cache: None,
+            font_cache: None,
             dll_hinstance: 0,
         }
     }
@@ -140,10 +254,53 @@ impl WindowsOptions {
 pub struct Windows {
     pub hwnd: HWND,
     window_tracker: WindowTracker,
+    tracking: TrackingOptions,
+    tracking_hook: Option<TrackingHook>,
     wc: WNDCLASSEXW,
     imgui: Context,
     window_is_active: bool,
+    /// 窗口当前是否带有WS_EX_TRANSPARENT，None表示尚未设置
+    click_through: Option<bool>,
     sync_interval: u32,
+    virtual_canvas: Option<VirtualCanvas>,
+    canvas_mapping: CanvasMapping,
+    theme_style: Option<Rc<dyn Fn(&mut Style)>>,
+    font_init: Option<Rc<dyn Fn(&mut Context, f32)>>,
+    font_manager: FontManager,
+    font_cache: Option<FontAtlasCache>,
+    font_options: FontOptions,
+    font_roles: FontRoles,
+    /// imgui的默认样式，切换主题时以此为基准
+    default_style: Style,
+    /// 未缩放的样式，渲染函数修改的是它的副本，DPI变化时以此为基准重新缩放
+    pristine_style: Style,
+    theme: Option<Theme>,
+    #[cfg(feature = "theme")]
+    theme_watcher: Option<ThemeWatcher>,
+    layout: Option<Layout>,
+    style_transition: Transition,
+    style_animation: Option<StyleAnimation>,
+    fade: Fade,
+    /// 完全淡出后已松开imgui中按下的按键与鼠标
+    input_suspended: bool,
+    dpi_scale: f32,
+    monitor: HMONITOR,
+    hotkeys: Hotkeys,
+    mode: OverlayMode,
+    /// 配置的模式，切换交互模式时回到该模式
+    base_mode: OverlayMode,
+    dim_background: Option<[f32; 4]>,
+    software_cursor: bool,
+    accept_dropped_files: bool,
+    hit_regions: HitRegions,
+    input: InputPipeline,
+    gamepad: Gamepad,
+    ime: Box<dyn ImeBackend>,
+    /// imgui最近一次的输入法要求，用于绘制组合文本
+    ime_request: ImeRequest,
+    /// 录制中的输入与保存路径
+    recording: Option<RecordingWriter>,
+    replay: Option<InputReplay>,
     #[allow(unused)]
     hinstance: HINSTANCE,
 }
@@ -151,13 +308,17 @@ pub struct Windows {
 impl Windows {
     /// 创建窗口与D3D渲染
     pub fn new(options: &WindowsOptions) -> Result<Windows> {
-        let target_hwnd = options.overlay_target.resolve_target_window()?;
+        let mut window_tracker =
+            WindowTracker::new(&options.overlay_target, options.anchor, options.margins)?;
+        if options.tracking.mode == TrackingMode::PerFrame {
+            window_tracker.prediction = options.tracking.prediction;
+        }
         unsafe {
             ImGui_ImplWin32_EnableDpiAwareness();
-            let hmonitor = MonitorFromPoint(
-                POINT { x: 0, y: 0 },
-                windows::Win32::Graphics::Gdi::MONITOR_FROM_FLAGS(0x1),
-            );
+            let hmonitor = match window_tracker.anchor_bounds() {
+                Some(bounds) => MonitorFromRect(&bounds, MONITOR_DEFAULTTONEAREST),
+                None => MonitorFromPoint(POINT { x: 0, y: 0 }, MONITOR_DEFAULTTOPRIMARY),
+            };
             let scale = ImGui_ImplWin32_GetDpiScaleForMonitor(hmonitor.0);
             let vec = PCWSTR(HSTRING::from(&options.title).as_ptr());
             let window_class = PCWSTR::from_raw(vec.as_ptr());
@@ -202,21 +363,76 @@ impl Windows {
                 return Err(result.err().unwrap());
             }
             ImGui_ImplWin32_EnableAlphaCompositing(hwnd.0);
+            if options.accept_dropped_files {
+                DragAcceptFiles(hwnd, true);
+            }
+            // 接收锁屏通知
+            if let Err(e) = WTSRegisterSessionNotification(hwnd, NOTIFY_FOR_THIS_SESSION) {
+                log::warn!("WTSRegisterSessionNotification failed: {e}");
+            }
             let _ = ShowWindow(hwnd, SW_SHOW);
             let _ = UpdateWindow(hwnd);
             let renderer = result?;
             let mut imgui_context = Context::create();
-            imgui_context.style_mut().use_classic_colors();
-            imgui_context.style_mut().colors[2] = [0.1, 0.1, 0.1, 1.];
-            imgui_context.style_mut().window_rounding = 5.0;
-            imgui_context.style_mut().scale_all_sizes(scale);
+            let default_style = *imgui_context.style();
             imgui_context.io_mut().config_flags |= ConfigFlags::NAV_ENABLE_KEYBOARD;
             imgui_context.io_mut().config_flags |= ConfigFlags::NAV_ENABLE_GAMEPAD;
             imgui_context.io_mut().config_flags |= ConfigFlags::NAV_ENABLE_SET_MOUSE_POS;
+            // 光标由Rust设置，避免C++后端在切换穿透样式时设置错误的光标
+            imgui_context.io_mut().config_flags |= ConfigFlags::NO_MOUSE_CURSOR_CHANGE;
+            // 布局由LayoutStorage读写
             imgui_context.set_ini_filename(None);
+            let layout = options.layout.as_ref().map(|layout_options| {
+                let target = options.overlay_target.identity(window_tracker.hwnd);
+                let layout = Layout::new(layout_options, target);
+                imgui_context.io_mut().ini_saving_rate = layout_options.save_interval.as_secs_f32();
+                layout.load(&mut imgui_context);
+                layout
+            });
+            // C++后端禁用了Win32函数，由Rust提供剪贴板
+            imgui_context.set_clipboard_backend(Win32Clipboard::new(hwnd));
+            ime::install(&mut imgui_context);
+            #[cfg(feature = "theme")]
+            let theme_watcher = options.theme_file.clone().map(ThemeWatcher::new);
+            #[cfg(feature = "theme")]
+            let theme = theme_watcher
+                .as_ref()
+                .and_then(ThemeWatcher::load)
+                .or_else(|| options.theme.clone());
+            #[cfg(not(feature = "theme"))]
+            let theme = options.theme.clone();
+            let mut font_options = options.fonts.clone();
+            if let Some(theme) = &theme {
+                theme.apply(imgui_context.style_mut());
+                theme.apply_fonts(&mut font_options);
+            }
+            if let Some(func) = &options.theme_style {
+                func(imgui_context.style_mut());
+            }
             if let Some(func) = &options.style_init {
-                func(&mut imgui_context)
+                let font_count = imgui_context.fonts().fonts().len();
+                func(&mut imgui_context);
+                if imgui_context.fonts().fonts().len() != font_count {
+                    log::warn!(
+                        "Fonts added in style_init are dropped when the font atlas is rebuilt, use font_init instead"
+                    );
+                }
+            }
+            let pristine_style = *imgui_context.style();
+            imgui_context.style_mut().scale_all_sizes(scale);
+            let mut font_manager = FontManager::new();
+            if let Some(path) = &options.glyph_cache {
+                font_manager.set_glyphs(DynamicGlyphs::with_cache(path));
             }
+            let font_cache = options.font_cache.as_ref().map(FontAtlasCache::new);
+            let font_roles = load_fonts(
+                &mut imgui_context,
+                options.font_init.as_ref(),
+                &mut font_manager,
+                font_cache.as_ref(),
+                &font_options,
+                scale,
+            );
             ImGui_ImplWin32_Init(hwnd.0);
             let (pd3d_device, ctx) = {
                 let device = renderer.pd3d_device.as_raw();
@@ -225,16 +441,86 @@ impl Windows {
             };
             ImGui_ImplDX11_Init(pd3d_device as _, ctx as _);
             *GLOBAL_DATA.lock().unwrap() = Some(renderer);
+            let mut hotkeys = options.hotkeys.clone();
+            if let Some(chord) = &options.mode_toggle {
+                hotkeys.bind(MODE_TOGGLE_ACTION, chord.clone());
+            }
+            if options.mode == OverlayMode::Passive {
+                imgui_context.io_mut().config_flags |= ConfigFlags::NO_MOUSE;
+            }
+            let (recording, replay) = match &options.input_capture {
+                InputCapture::None => (None, None),
+                InputCapture::Record(path) => match RecordingWriter::create(path) {
+                    Ok(recording) => (Some(recording), None),
+                    Err(e) => {
+                        log::error!("Failed to create input recording {}: {e}", path.display());
+                        (None, None)
+                    }
+                },
+                InputCapture::Replay(path) => match InputReplay::load(path) {
+                    Ok(replay) => (None, Some(replay)),
+                    Err(e) => {
+                        log::error!("Failed to load input recording {}: {e}", path.display());
+                        (None, None)
+                    }
+                },
+            };
+            // 显示器类目标不会移动，无需监听
+            let tracking_hook =
+                if options.tracking.needs_hook() && !options.overlay_target.is_display() {
+                    TrackingHook::start(
+                        &window_tracker,
+                        hwnd,
+                        options.tracking.mode == TrackingMode::EventDriven,
+                    )
+                } else {
+                    None
+                };
+            window_tracker.follow_events =
+                tracking_hook.is_some() && options.tracking.mode == TrackingMode::EventDriven;
             Ok(Windows {
                 hwnd,
-                window_tracker: WindowTracker {
-                    hwnd: target_hwnd,
-                    current_bounds: Default::default(),
-                },
+                window_tracker,
+                tracking: options.tracking,
+                tracking_hook,
                 wc,
                 imgui: imgui_context,
                 window_is_active: true,
+                click_through: None,
                 sync_interval: options.frame_rate.0,
+                virtual_canvas: options.virtual_canvas,
+                canvas_mapping: CanvasMapping::default(),
+                theme_style: options.theme_style.clone(),
+                font_init: options.font_init.clone(),
+                font_manager,
+                font_cache,
+                font_options,
+                font_roles,
+                default_style,
+                pristine_style,
+                theme,
+                #[cfg(feature = "theme")]
+                theme_watcher,
+                layout,
+                style_transition: options.style_transition,
+                style_animation: None,
+                fade: Fade::new(options.fade),
+                input_suspended: false,
+                dpi_scale: scale,
+                monitor: hmonitor,
+                hotkeys,
+                mode: options.mode,
+                base_mode: options.mode,
+                dim_background: options.dim_background,
+                software_cursor: options.software_cursor,
+                accept_dropped_files: options.accept_dropped_files,
+                hit_regions: HitRegions::default(),
+                input: InputPipeline::default(),
+                gamepad: Gamepad::new(options.gamepad),
+                ime: Box::new(Win32Ime::new(hwnd)),
+                ime_request: ImeRequest::default(),
+                recording,
+                replay,
                 hinstance: HINSTANCE(options.dll_hinstance as _),
             })
         }
@@ -244,10 +530,9 @@ impl Windows {
     /// [render] 渲染函数
     pub fn run<R>(&mut self, mut render: R) -> Result<()>
     where
-        R: FnMut(&mut Ui, &mut Style) -> bool + 'static,
+        R: FnMut(&mut Ui, &mut FrameContext) -> bool + 'static,
     {
         let mut exit = false;
-        let style = unsafe { &mut *(self.imgui.style_mut() as *mut Style) };
         loop {
             let mut message = MSG::default();
             while unsafe { PeekMessageA(&mut message, None, 0, 0, PM_REMOVE) } == TRUE {
@@ -263,6 +548,27 @@ impl Windows {
             if !self.window_tracker.tracking(self.hwnd) {
                 exit = true;
             }
+            self.update_canvas_mapping();
+            self.update_dpi_scale();
+            self.reload_theme();
+            let replay_frame = self.next_replay_frame();
+            // 录制与回放时热键按记录的按键状态更新
+            let keys_down = match &replay_frame {
+                Some(frame) => frame.keys_down.clone(),
+                None if self.recording.is_some() => record::keys_down(&Win32KeySource),
+                None => Vec::new(),
+            };
+            if replay_frame.is_some() || self.recording.is_some() {
+                self.hotkeys.update(&RecordedKeys(&keys_down));
+            } else {
+                self.hotkeys.update(&Win32KeySource);
+            }
+            if self.hotkeys.pressed(MODE_TOGGLE_ACTION) {
+                self.toggle_mode();
+            }
+            if let Some(mode) = replay_frame.as_ref().and_then(|frame| frame.mode) {
+                self.set_mode(mode);
+            }
             if exit {
                 break;
             }
@@ -271,12 +577,108 @@ impl Windows {
                 ImGui_ImplDX11_NewFrame();
                 ImGui_ImplWin32_NewFrame();
             }
+            self.input.extend(input::take_win32_events());
+            self.update_gamepad();
             {
                 self.imgui_active_check()?;
             }
-            {
+            self.replay_input(replay_frame.as_ref());
+            self.request_input_glyphs();
+            if let Some(recording) = &mut self.recording {
+                // 记录钩子过滤前的事件，回放时钩子会再次看到相同的序列
+                let io = self.imgui.io();
+                let frame = RecordedFrame {
+                    delta_time: io.delta_time,
+                    display_size: io.display_size,
+                    mode: Some(self.mode),
+                    keys_down,
+                    events: self.input.pending().to_vec(),
+                };
+                if let Err(e) = recording.write_frame(&frame) {
+                    let path = recording.path().display();
+                    log::error!("Failed to write input recording {path}: {e}");
+                    self.recording = None;
+                }
+            }
+            if self.fade.is_hidden() {
+                self.suspend_input();
+            } else {
+                self.input_suspended = false;
+                self.input.dispatch(self.imgui.io_mut());
+            }
+            self.update_transitions();
+            // 渲染函数拿到的是缩放前的目标样式的副本，修改在下一帧开始前写回
+            let style = *self.style();
+            // 淡入淡出只作用于本帧，渲染后恢复
+            let alpha = self.imgui.style().alpha;
+            self.imgui.style_mut().alpha = alpha * self.fade.alpha();
+            let (
+                requested_mode,
+                requested_visible,
+                requested_fonts,
+                requested_theme,
+                requested_style,
+            ) = {
                 let frame = self.imgui.new_frame();
-                exit = !render(frame, style)
+                if let (OverlayMode::Interactive, Some(color)) = (self.mode, self.dim_background) {
+                    frame
+                        .get_background_draw_list()
+                        .add_rect([0.0, 0.0], frame.io().display_size, color)
+                        .filled(true)
+                        .build();
+                }
+                let mut context = FrameContext {
+                    style,
+                    requested_style: None,
+                    visible: self.fade.is_visible(),
+                    requested_visible: None,
+                    canvas: self.canvas_mapping,
+                    dpi_scale: self.dpi_scale,
+                    fonts: &self.font_roles,
+                    font_options: &self.font_options,
+                    requested_fonts: None,
+                    glyphs: self.font_manager.glyphs_mut(),
+                    requested_theme: None,
+                    hotkeys: &mut self.hotkeys,
+                    hit_regions: &mut self.hit_regions,
+                    mode: self.mode,
+                    base_mode: self.base_mode,
+                    requested_mode: None,
+                };
+                exit = !render(frame, &mut context);
+                let requested_mode = context.requested_mode();
+                let requested_visible = context.requested_visible();
+                let requested_fonts = context.take_requested_fonts();
+                let requested_theme = context.take_requested_theme();
+                let requested_style = context.take_requested_style();
+                let mouse_cursor = frame.mouse_cursor();
+                if self.ime_request.active {
+                    if let Some(composition) = self.ime.composition() {
+                        ime::draw_composition(frame, &self.ime_request, &composition);
+                    }
+                }
+                // 穿透时光标属于目标窗口，不做修改
+                if self.window_is_active {
+                    cursor::set_cursor(if self.software_cursor {
+                        None
+                    } else {
+                        mouse_cursor
+                    });
+                }
+                (
+                    requested_mode,
+                    requested_visible,
+                    requested_fonts,
+                    requested_theme,
+                    requested_style,
+                )
+            };
+            self.hit_regions.end_frame();
+            if let Some(mode) = requested_mode {
+                self.set_mode(mode);
+            }
+            if let Some(visible) = requested_visible {
+                self.set_visible(visible);
             }
             let mut guard = GLOBAL_DATA.lock().unwrap();
             if let Some(ref mut renderer) = *guard {
@@ -288,13 +690,45 @@ impl Windows {
                     renderer
                         .pd3d_device_context
                         .ClearRenderTargetView(&view, &[0f32; 4]);
-                    ImGui_ImplDX11_RenderDrawData(self.imgui.render());
+                    let draw_data = self.imgui.render();
+                    let dragging = self.tracking.hide_while_dragging && is_dragging();
+                    if !dragging && !self.fade.is_hidden() {
+                        ImGui_ImplDX11_RenderDrawData(draw_data);
+                    }
                     let _ = renderer
                         .p_swap_chain
                         .Present(self.sync_interval, DXGI_PRESENT(0));
                     renderer.p_main_render_target_view = Some(view);
                 }
             }
+            drop(guard);
+            self.imgui.style_mut().alpha = alpha;
+            self.update_ime();
+            // 样式修改基于切换前的样式，与主题同时请求时以主题为准
+            if let Some(theme) = requested_theme {
+                if requested_style.is_some() {
+                    log::warn!("Ignoring style edits made after set_theme in the same frame");
+                }
+                self.set_theme(theme);
+            } else if let Some(style) = requested_style {
+                self.set_style(style);
+            }
+            // 本帧的绘制数据引用旧的字体纹理，呈现后再重建
+            if let Some(options) = requested_fonts {
+                self.set_font_options(options);
+            }
+            self.load_pending_glyphs();
+            if let Some(layout) = &self.layout {
+                layout.save_if_requested(&mut self.imgui);
+            }
+        }
+        if let Err(e) = self.save_layout() {
+            log::error!("Failed to save layout: {e}");
+        }
+        drop(self.tracking_hook.take());
+        message::clear_hooks();
+        unsafe {
+            let _ = WTSUnRegisterSessionNotification(self.hwnd);
         }
         unsafe {
             ImGui_ImplDX11_Shutdown();
@@ -313,44 +747,422 @@ impl Windows {
         Ok(())
     }
 
+    /// 当前交互模式
+    pub fn mode(&self) -> OverlayMode {
+        self.mode
+    }
+
+    /// 设置交互模式，下一帧生效
+    pub fn set_mode(&mut self, mode: OverlayMode) {
+        if mode == self.mode {
+            return;
+        }
+        log::debug!("Overlay mode changed from {:?} to {:?}", self.mode, mode);
+        self.mode = mode;
+        let io = self.imgui.io_mut();
+        if mode == OverlayMode::Passive {
+            io.config_flags |= ConfigFlags::NO_MOUSE;
+        } else {
+            io.config_flags &= !ConfigFlags::NO_MOUSE;
+        }
+    }
+
+    /// 隐藏后丢弃输入，第一次丢弃时松开imgui中仍按下的按键与鼠标
+    fn suspend_input(&mut self) {
+        self.input.clear();
+        if self.input_suspended {
+            return;
+        }
+        self.input_suspended = true;
+        let io = self.imgui.io_mut();
+        InputEvent::Focus(false).apply(io);
+        for button in MouseButton::VARIANTS {
+            io.add_mouse_button_event(button, false);
+        }
+        io.add_mouse_pos_event([-f32::MAX, -f32::MAX]);
+    }
+
+    /// 可交互区域，可在运行前注册固定区域
+    pub fn hit_regions(&mut self) -> &mut HitRegions {
+        &mut self.hit_regions
+    }
+
+    /// 设置输入钩子，事件转发给imgui之前调用，返回true表示消费该事件
+    pub fn set_input_hook(&mut self, hook: impl FnMut(&InputEvent) -> bool + 'static) {
+        self.input.set_hook(hook);
+    }
+
+    /// 注入输入事件，下一帧转发给imgui
+    pub fn push_input(&mut self, event: InputEvent) {
+        self.input.push(event);
+    }
+
+    /// 替换剪贴板，默认使用系统剪贴板
+    pub fn set_clipboard_backend(&mut self, backend: impl ClipboardBackend) {
+        self.imgui.set_clipboard_backend(backend);
+    }
+
+    /// 注册窗口消息钩子，钩子返回Some时消费消息并作为窗口过程的返回值
+    pub fn add_message_hook(
+        &mut self,
+        stage: MessageStage,
+        hook: impl FnMut(&WindowMessage) -> Option<isize> + 'static,
+    ) -> MessageHookId {
+        message::add_hook(stage, Box::new(hook))
+    }
+
+    pub fn remove_message_hook(&mut self, id: MessageHookId) -> bool {
+        message::remove_hook(id)
+    }
+
+    /// 是否由imgui绘制光标
+    pub fn set_software_cursor(&mut self, enabled: bool) {
+        self.software_cursor = enabled;
+    }
+
+    /// 替换输入法后端，例如使用[crate::MockIme]测试
+    pub fn set_ime_backend(&mut self, backend: impl ImeBackend + 'static) {
+        self.ime = Box::new(backend);
+    }
+
+    /// 布局的键，未启用布局持久化时为None
+    pub fn layout_key(&self) -> Option<&str> {
+        self.layout.as_ref().map(Layout::key)
+    }
+
+    /// 立即保存布局，未启用布局持久化时不做任何事
+    pub fn save_layout(&mut self) -> io::Result<()> {
+        match &self.layout {
+            Some(layout) => layout.save(&mut self.imgui),
+            None => Ok(()),
+        }
+    }
+
+    /// 当前样式，尺寸为DPI缩放前的值，过渡期间为过渡的目标
+    pub fn style(&self) -> &Style {
+        &self.pristine_style
+    }
+
+    /// 替换样式，尺寸为DPI缩放前的值，渲染时改用[FrameContext::style]。
+    /// 不做过渡，主题过渡期间只替换过渡的目标，每帧写入样式的控件不会打断主题过渡
+    pub fn set_style(&mut self, style: Style) {
+        self.pristine_style = style;
+        let mut scaled = style;
+        scaled.scale_all_sizes(self.dpi_scale);
+        match &mut self.style_animation {
+            Some(animation) => animation.set_target(scaled),
+            None => *self.imgui.style_mut() = scaled,
+        }
+    }
+
+    /// 切换主题时的过渡动画
+    pub fn set_style_transition(&mut self, transition: Transition) {
+        self.style_transition = transition;
+    }
+
+    /// 按[WindowsOptions::style_transition]从当前样式过渡到[style]
+    fn transition_style(&mut self, style: Style) {
+        if self.style_transition.is_instant() {
+            self.style_animation = None;
+            *self.imgui.style_mut() = style;
+        } else {
+            let from = *self.imgui.style();
+            self.style_animation = Some(StyleAnimation::new(from, style, self.style_transition));
+        }
+    }
+
+    /// 推进样式过渡与淡入淡出
+    fn update_transitions(&mut self) {
+        let delta_time = self.imgui.io().delta_time;
+        if let Some(animation) = &mut self.style_animation {
+            *self.imgui.style_mut() = animation.advance(delta_time);
+            if animation.is_finished() {
+                self.style_animation = None;
+            }
+        }
+        self.fade.advance(delta_time);
+    }
+
+    /// 覆盖内容是否显示，淡出过程中已为false
+    pub fn is_visible(&self) -> bool {
+        self.fade.is_visible()
+    }
+
+    /// 显示或隐藏覆盖内容，按[WindowsOptions::fade]淡入淡出。
+    /// 完全淡出后仍然调用渲染函数，但窗口对鼠标穿透，输入与手柄不再交给imgui
+    pub fn set_visible(&mut self, visible: bool) {
+        if visible != self.fade.is_visible() {
+            log::debug!("Overlay visibility changed to {visible}");
+            self.fade.set_visible(visible);
+        }
+    }
+
+    /// 显示与隐藏时的淡入淡出
+    pub fn set_fade(&mut self, transition: Transition) {
+        self.fade.set_transition(transition);
+    }
+
+    /// 当前主题
+    pub fn theme(&self) -> Option<&Theme> {
+        self.theme.as_ref()
+    }
+
+    /// 以imgui的默认样式为基准应用主题并再次调用[WindowsOptions::theme_style]，
+    /// 覆盖运行时对样式的修改，渲染时改用[FrameContext::set_theme]
+    pub fn set_theme(&mut self, theme: Theme) {
+        log::debug!("Applying theme {:?}", theme.name);
+        let mut style = self.default_style;
+        theme.apply(&mut style);
+        if let Some(func) = &self.theme_style {
+            func(&mut style);
+        }
+        self.pristine_style = style;
+        style.scale_all_sizes(self.dpi_scale);
+        self.transition_style(style);
+        let mut font_options = self.font_options.clone();
+        if theme.apply_fonts(&mut font_options) {
+            self.set_font_options(font_options);
+        }
+        self.theme = Some(theme);
+    }
+
+    /// 当前的字体配置
+    pub fn font_options(&self) -> &FontOptions {
+        &self.font_options
+    }
+
+    /// 替换字体配置并重建字体图集，渲染时改用[FrameContext::set_font_options]
+    pub fn set_font_options(&mut self, options: FontOptions) {
+        if options == self.font_options {
+            return;
+        }
+        self.font_options = options;
+        self.rebuild_fonts(true);
+    }
+
+    /// 替换手柄输入来源，例如使用[crate::MockGamepad]测试
+    pub fn set_gamepad_source(&mut self, source: impl GamepadSource + 'static) {
+        self.gamepad.set_source(Box::new(source));
+    }
+
+    /// 在交互模式与配置的模式之间切换
+    pub fn toggle_mode(&mut self) {
+        self.set_mode(self.mode.toggled(self.base_mode));
+    }
+
+    /// 读取手柄，只在交互模式下交给imgui导航，其它模式或隐藏时手柄属于目标程序
+    fn update_gamepad(&mut self) {
+        let forward = self.mode == OverlayMode::Interactive && !self.fade.is_hidden();
+        if self.gamepad.update(&mut self.input, forward) {
+            self.toggle_mode();
+        }
+        let io = self.imgui.io_mut();
+        if forward && self.gamepad.is_connected() {
+            io.backend_flags |= BackendFlags::HAS_GAMEPAD;
+        } else {
+            io.backend_flags &= !BackendFlags::HAS_GAMEPAD;
+        }
+    }
+
+    /// 将imgui的输入法要求交给后端
+    fn update_ime(&mut self) {
+        let Some(request) = ime::take_request() else {
+            return;
+        };
+        self.ime.update(&request);
+        // 文本框获得焦点时需要键盘焦点才能使用输入法
+        if request.active && !self.ime_request.active && self.window_is_active {
+            unsafe {
+                let _ = SetForegroundWindow(self.hwnd);
+            }
+        }
+        self.ime_request = request;
+    }
+
+    /// 回放的下一帧，回放结束后恢复实时输入
+    fn next_replay_frame(&mut self) -> Option<RecordedFrame> {
+        let replay = self.replay.as_mut()?;
+        match replay.next_frame() {
+            Some(frame) => Some(frame.clone()),
+            None => {
+                log::info!("Input replay finished after {} frames", replay.len());
+                self.replay = None;
+                None
+            }
+        }
+    }
+
+    /// 回放期间以录制的输入、帧间隔与窗口大小替换实时输入
+    fn replay_input(&mut self, frame: Option<&RecordedFrame>) {
+        let Some(frame) = frame else {
+            return;
+        };
+        self.input.clear();
+        let io = self.imgui.io_mut();
+        io.delta_time = frame.delta_time.max(f32::EPSILON);
+        if frame.display_size != [0.0, 0.0] {
+            io.display_size = frame.display_size;
+        }
+        self.input.extend(frame.events.iter().cloned());
+    }
+
+    /// 覆盖窗口大小变化后重新计算虚拟画布映射
+    fn update_canvas_mapping(&mut self) {
+        let bounds = &self.window_tracker.current_bounds;
+        let size = [
+            (bounds.right - bounds.left) as f32,
+            (bounds.bottom - bounds.top) as f32,
+        ];
+        if size == self.canvas_mapping.overlay_size() {
+            return;
+        }
+        self.canvas_mapping = match &self.virtual_canvas {
+            Some(canvas) => CanvasMapping::new(canvas, size),
+            None => CanvasMapping::identity(size),
+        };
+    }
+
+    /// 主题文件变化后重新应用
+    #[cfg(feature = "theme")]
+    fn reload_theme(&mut self) {
+        if let Some(theme) = self.theme_watcher.as_mut().and_then(ThemeWatcher::poll) {
+            self.set_theme(theme);
+        }
+    }
+
+    #[cfg(not(feature = "theme"))]
+    fn reload_theme(&mut self) {}
+
+    /// 目标移动到其它显示器或显示器DPI变化后重新缩放样式与字体
+    fn update_dpi_scale(&mut self) {
+        let dpi_changed = DPI_CHANGED.swap(false, Ordering::AcqRel);
+        let bounds = self.window_tracker.current_bounds;
+        let monitor = unsafe { MonitorFromRect(&bounds, MONITOR_DEFAULTTONEAREST) };
+        if monitor == self.monitor && !dpi_changed {
+            return;
+        }
+        self.monitor = monitor;
+        let scale = unsafe { ImGui_ImplWin32_GetDpiScaleForMonitor(monitor.0) };
+        if scale == self.dpi_scale {
+            return;
+        }
+        log::debug!("DPI scale changed from {} to {}", self.dpi_scale, scale);
+        // 过渡的两端按旧的DPI缩放，直接跳到目标
+        if let Some(animation) = self.style_animation.take() {
+            *self.imgui.style_mut() = *animation.target();
+        }
+        let style = self.imgui.style_mut();
+        // 保留运行时修改过的颜色，只重置尺寸
+        let colors = style.colors;
+        *style = self.pristine_style;
+        style.colors = colors;
+        style.scale_all_sizes(scale);
+        self.dpi_scale = scale;
+        self.rebuild_fonts(true);
+    }
+
+    /// 按当前配置与DPI缩放重建字体图集，只能在两帧之间调用。
+    /// 按需加载字符后的图集每次都不同，[cached]为false时不读写磁盘缓存
+    fn rebuild_fonts(&mut self, cached: bool) {
+        self.imgui.fonts().clear();
+        self.font_roles = load_fonts(
+            &mut self.imgui,
+            self.font_init.as_ref(),
+            &mut self.font_manager,
+            self.font_cache.as_ref().filter(|_| cached),
+            &self.font_options,
+            self.dpi_scale,
+        );
+        // 下一帧NewFrame时重新上传字体纹理
+        unsafe { ImGui_ImplDX11_InvalidateDeviceObjects() };
+    }
+
+    /// 输入的文字与输入法组合文本需要的字符
+    fn request_input_glyphs(&mut self) {
+        let glyphs = self.font_manager.glyphs_mut();
+        for event in self.input.pending() {
+            if let InputEvent::Text(c) = event {
+                glyphs.request(c.encode_utf8(&mut [0; 4]));
+            }
+        }
+        if let Some(composition) = self.ime.composition() {
+            glyphs.request(&composition.text);
+        }
+    }
+
+    /// 将本帧缺失的字符加入字体图集，只能在两帧之间调用
+    fn load_pending_glyphs(&mut self) {
+        if !self.font_manager.glyphs().has_pending() {
+            return;
+        }
+        self.font_manager.glyphs_mut().commit();
+        if self.font_options.uses_dynamic_glyphs() {
+            self.rebuild_fonts(false);
+        }
+    }
+
     /// imgui窗口检查
     #[inline]
     fn imgui_active_check(&mut self) -> Result<()> {
         {
-            let io = self.imgui.io_mut();
+            let io = self.imgui.io();
             // 活动检查 1.鼠标输入事件 2.鼠标按键事件
-            {
+            let cursor = {
                 let mut point = POINT::default();
                 unsafe {
                     let _ = GetCursorPos(&mut point)?;
                     let _ = ScreenToClient(self.hwnd, &mut point);
                 };
-                io.add_mouse_pos_event([point.x as _, point.y as _]);
+                let cursor = [point.x as f32, point.y as f32];
+                self.input.push(InputEvent::MouseMove { pos: cursor });
+                cursor
+            };
+            let hidden = self.fade.is_hidden();
+            let imgui_active = !hidden
+                && match self.mode {
+                    OverlayMode::Passive => false,
+                    OverlayMode::Interactive => true,
+                    OverlayMode::Auto => io.want_capture_mouse,
+                    OverlayMode::Regions => self.hit_regions.contains(cursor),
+                };
+            // 只在捕获鼠标时绘制软件光标，穿透时光标属于目标窗口
+            self.imgui.io_mut().mouse_draw_cursor = self.software_cursor && imgui_active;
+            // 目标窗口属于其它进程，只能切换整个窗口的穿透，区域模式按本帧的光标位置切换
+            let click_through = !imgui_active;
+            if self.click_through != Some(click_through) {
+                self.click_through = Some(click_through);
+                let accept_files = if self.accept_dropped_files {
+                    WS_EX_ACCEPTFILES
+                } else {
+                    WINDOW_EX_STYLE::default()
+                };
+                let transparent = if click_through {
+                    WS_EX_TRANSPARENT
+                } else {
+                    WINDOW_EX_STYLE::default()
+                };
+                unsafe {
+                    let _ = SetWindowLongA(
+                        self.hwnd,
+                        GWL_EXSTYLE,
+                        (WS_EX_TOPMOST
+                            | transparent
+                            | WS_EX_LAYERED
+                            | WS_EX_TOOLWINDOW
+                            | accept_files)
+                            .0 as _,
+                    );
+                }
             }
-            let imgui_active = io.want_capture_mouse;
             if imgui_active != self.window_is_active {
                 self.window_is_active = imgui_active;
-                if imgui_active {
-                    unsafe {
-                        let _ = SetWindowLongA(
-                            self.hwnd,
-                            GWL_EXSTYLE,
-                            (WS_EX_TOPMOST | WS_EX_LAYERED | WS_EX_TOOLWINDOW).0 as _,
-                        );
-                    }
-                } else {
-                    unsafe {
-                        let _ = SetWindowLongA(
-                            self.hwnd,
-                            GWL_EXSTYLE,
-                            (WS_EX_TOPMOST | WS_EX_TRANSPARENT | WS_EX_LAYERED | WS_EX_TOOLWINDOW).0
-                                as _,
-                        );
-                    }
-                }
                 if imgui_active {
                     unsafe {
                         let _ = SetActiveWindow(self.hwnd);
+                        // 交互模式需要键盘焦点
+                        if self.mode == OverlayMode::Interactive {
+                            let _ = SetForegroundWindow(self.hwnd);
+                        }
                     };
                 }
             }
@@ -379,6 +1191,44 @@ impl Windows {
     fn free(&self) {}
 }
 
+/// 加入字体，返回各角色的字体
+fn load_fonts(
+    imgui: &mut Context,
+    font_init: Option<&Rc<dyn Fn(&mut Context, f32)>>,
+    font_manager: &mut FontManager,
+    font_cache: Option<&FontAtlasCache>,
+    options: &FontOptions,
+    scale: f32,
+) -> FontRoles {
+    let roles = add_fonts(imgui, font_init, font_manager, options, scale);
+    if let Some(cache) = font_cache {
+        cache.build(imgui.fonts());
+    }
+    roles
+}
+
+fn add_fonts(
+    imgui: &mut Context,
+    font_init: Option<&Rc<dyn Fn(&mut Context, f32)>>,
+    font_manager: &mut FontManager,
+    options: &FontOptions,
+    scale: f32,
+) -> FontRoles {
+    let Some(func) = font_init else {
+        return font_manager.build(imgui, options, scale);
+    };
+    func(imgui, scale);
+    let font = match imgui.fonts().fonts().first() {
+        Some(font) => *font,
+        None => imgui
+            .fonts()
+            .add_font(&[FontSource::DefaultFontData { config: None }]),
+    };
+    let mut roles = FontRoles::uniform(font);
+    font_manager.add_extra(imgui, options, scale, &mut roles);
+    roles
+}
+
 #[cfg(feature = "lib")]
 unsafe extern "system" fn free_func(lpthreadparameter: *mut core::ffi::c_void) -> u32 {
     {
@@ -395,9 +1245,38 @@ unsafe extern "system" fn free_func(lpthreadparameter: *mut core::ffi::c_void) -
 
 extern "system" fn wndproc(window: HWND, message: u32, wparam: WPARAM, lparam: LPARAM) -> LRESULT {
     unsafe {
-        let handler = ImGui_ImplWin32_WndProcHandler(window.0, message, wparam, lparam);
-        if handler.0 > 0 {
-            return handler;
+        if message == WM_DROPFILES {
+            // 文件列表已复制到事件中，无论钩子是否消费都需要释放
+            let result =
+                message::dispatch(MessageStage::BeforeImgui, window, message, wparam, lparam)
+                    .or_else(|| {
+                        message::dispatch(MessageStage::AfterImgui, window, message, wparam, lparam)
+                    });
+            DragFinish(HDROP(wparam.0 as _));
+            return result.unwrap_or(LRESULT(0));
+        }
+        if let Some(result) =
+            message::dispatch(MessageStage::BeforeImgui, window, message, wparam, lparam)
+        {
+            return result;
+        }
+        if cursor::handle_win32_message(message, lparam) {
+            return LRESULT(1);
+        }
+        if let Some(result) = ime::handle_win32_message(window, message, wparam, lparam) {
+            return result;
+        }
+        // 输入消息由Rust转换为InputEvent，其余消息(光标、设备变化)仍交给imgui后端
+        if !input::handle_win32_message(window, message, wparam, lparam) {
+            let handler = ImGui_ImplWin32_WndProcHandler(window.0, message, wparam, lparam);
+            if handler.0 > 0 {
+                return handler;
+            }
+        }
+        if let Some(result) =
+            message::dispatch(MessageStage::AfterImgui, window, message, wparam, lparam)
+        {
+            return result;
         }
         match message {
             WM_PAINT => {
@@ -422,6 +1301,19 @@ extern "system" fn wndproc(window: HWND, message: u32, wparam: WPARAM, lparam: L
                 }
                 return LRESULT(0);
             }
+            WM_DPICHANGED => {
+                // 覆盖窗口的位置与大小由跟踪器决定，这里只记录变化
+                DPI_CHANGED.store(true, Ordering::Release);
+                LRESULT(0)
+            }
+            WM_DEVICECHANGE => {
+                gamepad::notify_device_change();
+                DefWindowProcW(window, message, wparam, lparam)
+            }
+            WM_DISPLAYCHANGE => {
+                DISPLAY_CHANGED.store(true, Ordering::Release);
+                DefWindowProcW(window, message, wparam, lparam)
+            }
             WM_SYSCOMMAND => {
                 if ((wparam.0 & 0xfff0) as u32) == SC_KEYMENU {
                     LRESULT(0)
@@ -2,20 +2,30 @@ use windows::{
     core::{Result, PCWSTR},
     Win32::{
         Foundation::{
-            GetLastError, ERROR_INVALID_WINDOW_HANDLE, HWND, LPARAM, POINT, RECT, WPARAM,
+            CloseHandle, GetLastError, ERROR_INVALID_WINDOW_HANDLE, HWND, LPARAM, POINT, RECT,
+            WPARAM,
         },
-        Graphics::Gdi::ClientToScreen,
-        UI::{
-            Input::KeyboardAndMouse::GetFocus,
-            WindowsAndMessaging::{
-                FindWindowExA, FindWindowW, GetClientRect, GetWindowRect, GetWindowThreadProcessId,
-                IsWindow, MoveWindow, SendMessageA, SetWindowPos, WM_PAINT,
-            },
+        Graphics::{
+            Dwm::{DwmGetWindowAttribute, DWMWA_EXTENDED_FRAME_BOUNDS},
+            Gdi::ClientToScreen,
+        },
+        System::Threading::{
+            OpenProcess, QueryFullProcessImageNameW, PROCESS_NAME_WIN32,
+            PROCESS_QUERY_LIMITED_INFORMATION,
+        },
+        UI::WindowsAndMessaging::{
+            FindWindowExA, FindWindowW, GetClientRect, GetDesktopWindow, GetDlgItem, GetWindowRect,
+            GetWindowThreadProcessId, IsWindow, MoveWindow, SendMessageA, SetWindowPos,
+            SWP_ASYNCWINDOWPOS, SWP_NOACTIVATE, SWP_NOZORDER, WM_PAINT,
         },
     },
 };
 
-use windows::core::HSTRING;
+use windows::core::{HSTRING, PWSTR};
+
+use crate::monitor::{
+    monitors, primary_monitor, take_display_changed, virtual_desktop_bounds, MonitorSelector,
+};
 
 /// 附加的窗口宽高(该属性只允许读，不允许写)
 pub static mut WINDOWS_RECT: Rect = Rect { width: 0, high: 0 };
@@ -25,16 +35,163 @@ pub struct Rect {
     pub high: i32,
 }
 
+/// 覆盖区域四周的内边距(像素)
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Margins {
+    pub left: i32,
+    pub top: i32,
+    pub right: i32,
+    pub bottom: i32,
+}
+
+impl Margins {
+    /// 四周相同的边距
+    pub const fn all(value: i32) -> Margins {
+        Margins {
+            left: value,
+            top: value,
+            right: value,
+            bottom: value,
+        }
+    }
+}
+
+/// 目标客户区内的自定义区域
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum AnchorRect {
+    /// 相对客户区左上角的像素坐标
+    Pixels {
+        x: i32,
+        y: i32,
+        width: i32,
+        height: i32,
+    },
+    /// 相对客户区大小的比例坐标(0.0 ~ 1.0)
+    Percent {
+        x: f32,
+        y: f32,
+        width: f32,
+        height: f32,
+    },
+}
+
+impl AnchorRect {
+    /// 在宽高为`width`x`height`的客户区内解析为`(x, y, width, height)`
+    pub fn resolve(&self, width: i32, height: i32) -> (i32, i32, i32, i32) {
+        match *self {
+            AnchorRect::Pixels {
+                x,
+                y,
+                width,
+                height,
+            } => (x, y, width, height),
+            AnchorRect::Percent {
+                x,
+                y,
+                width: w,
+                height: h,
+            } => (
+                (x * width as f32).round() as i32,
+                (y * height as f32).round() as i32,
+                (w * width as f32).round() as i32,
+                (h * height as f32).round() as i32,
+            ),
+        }
+    }
+}
+
+/// 覆盖窗口在目标窗口上锚定的区域
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum OverlayAnchor {
+    /// 客户区(默认)
+    #[default]
+    Client,
+    /// 整个窗口，包含边框与标题栏
+    Window,
+    /// 子控件的客户区
+    Child(HWND),
+    /// 通过控件ID查找的子控件客户区
+    ChildId(i32),
+    /// 客户区内的自定义区域
+    Custom(AnchorRect),
+}
+
 pub enum OverlayTarget {
     Window(HWND),
     WindowTitle(String),
     WindowOfProcess(u32),
+    /// 指定显示器
+    Monitor(MonitorSelector),
+    /// 主显示器
+    PrimaryMonitor,
+    /// 覆盖所有显示器的虚拟桌面
+    VirtualDesktop,
+}
+
+/// 显示器类目标，显示器配置变化时重新解析
+#[derive(Clone, Debug)]
+enum DisplayTarget {
+    Monitor(MonitorSelector),
+    Primary,
+    VirtualDesktop,
+}
+
+impl DisplayTarget {
+    fn bounds(&self) -> Option<RECT> {
+        match self {
+            DisplayTarget::Monitor(selector) => {
+                let monitors = monitors();
+                match selector.find(&monitors) {
+                    Some(monitor) => Some(monitor.bounds),
+                    None => {
+                        log::warn!("Monitor {:?} not found, falling back to primary", selector);
+                        primary_monitor().map(|monitor| monitor.bounds)
+                    }
+                }
+            }
+            DisplayTarget::Primary => primary_monitor().map(|monitor| monitor.bounds),
+            DisplayTarget::VirtualDesktop => Some(virtual_desktop_bounds()),
+        }
+    }
 }
 
 impl OverlayTarget {
+    fn display_target(&self) -> Option<DisplayTarget> {
+        match self {
+            Self::Monitor(selector) => Some(DisplayTarget::Monitor(selector.clone())),
+            Self::PrimaryMonitor => Some(DisplayTarget::Primary),
+            Self::VirtualDesktop => Some(DisplayTarget::VirtualDesktop),
+            _ => None,
+        }
+    }
+
+    /// 是否为显示器类目标
+    pub(crate) fn is_display(&self) -> bool {
+        self.display_target().is_some()
+    }
+
+    /// 跨次运行不变的目标标识，用于按目标保存布局。窗口类目标取所属进程的exe名称
+    pub(crate) fn identity(&self, hwnd: HWND) -> String {
+        match self {
+            Self::Monitor(MonitorSelector::Index(index)) => format!("monitor-{index}"),
+            Self::Monitor(MonitorSelector::Name(name)) => {
+                format!("monitor-{}", name.trim_start_matches(r"\\.\"))
+            }
+            Self::PrimaryMonitor => String::from("primary-monitor"),
+            Self::VirtualDesktop => String::from("virtual-desktop"),
+            Self::WindowTitle(title) => process_name(hwnd).unwrap_or_else(|| title.clone()),
+            Self::Window(_) | Self::WindowOfProcess(_) => {
+                process_name(hwnd).unwrap_or_else(|| String::from("window"))
+            }
+        }
+    }
+
     pub(crate) fn resolve_target_window(&self) -> Result<HWND> {
         Ok(match self {
             Self::Window(hwnd) => *hwnd,
+            Self::Monitor(_) | Self::PrimaryMonitor | Self::VirtualDesktop => unsafe {
+                GetDesktopWindow()
+            },
             Self::WindowTitle(title) => unsafe {
                 FindWindowW(
                     PCWSTR::null(),
@@ -93,36 +250,126 @@ impl OverlayTarget {
     }
 }
 
+/// 窗口所属进程的exe名称，不含扩展名
+fn process_name(hwnd: HWND) -> Option<String> {
+    let mut process_id = 0;
+    if unsafe { GetWindowThreadProcessId(hwnd, Some(&mut process_id)) } == 0 {
+        return None;
+    }
+    let process =
+        unsafe { OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, false, process_id) }.ok()?;
+    let mut buffer = [0u16; 1024];
+    let mut len = buffer.len() as u32;
+    let result = unsafe {
+        QueryFullProcessImageNameW(
+            process,
+            PROCESS_NAME_WIN32,
+            PWSTR(buffer.as_mut_ptr()),
+            &mut len,
+        )
+    };
+    unsafe {
+        let _ = CloseHandle(process);
+    }
+    result.ok()?;
+    let path = String::from_utf16_lossy(&buffer[..len as usize]);
+    std::path::Path::new(&path)
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_lowercase())
+}
+
 /// Track the window and adjust overlay accordingly.
 /// This is only required when playing in windowed mode.
+#[derive(Clone)]
 pub struct WindowTracker {
     pub hwnd: HWND,
     pub current_bounds: RECT,
+    /// 锚定区域
+    pub anchor: OverlayAnchor,
+    /// 锚定区域的内边距
+    pub margins: Margins,
+    /// 位置预测的帧数，0表示不预测
+    pub prediction: f32,
+    /// 监听线程已在目标移动时移动覆盖窗口，[WindowTracker::tracking]只更新边界
+    pub(crate) follow_events: bool,
+    display: Option<DisplayTarget>,
+    display_bounds: Option<RECT>,
+    /// 上一帧目标的实际位置，用于计算移动速度
+    last_target_bounds: RECT,
 }
 
 impl WindowTracker {
-    /// 跟踪窗口
-    #[allow(dead_code)]
-    pub fn update(&mut self, hwnd: HWND) -> bool {
-        let mut rect: RECT = Default::default();
-        let success = unsafe { GetClientRect(self.hwnd, &mut rect) };
-        if success.is_err() {
-            let error = unsafe { GetLastError() };
-            if error == ERROR_INVALID_WINDOW_HANDLE {
-                return false;
+    pub fn new(
+        target: &OverlayTarget,
+        anchor: OverlayAnchor,
+        margins: Margins,
+    ) -> Result<WindowTracker> {
+        Ok(WindowTracker {
+            hwnd: target.resolve_target_window()?,
+            current_bounds: Default::default(),
+            anchor,
+            margins,
+            prediction: 0.0,
+            follow_events: false,
+            display: target.display_target(),
+            display_bounds: None,
+            last_target_bounds: Default::default(),
+        })
+    }
+
+    /// 计算锚定区域的屏幕坐标，区域不可用时返回`None`
+    pub fn anchor_bounds(&mut self) -> Option<RECT> {
+        let mut rect = if self.display.is_some() {
+            // 显示器没有子控件与边框，仅自定义区域有意义
+            let screen = self.display_bounds()?;
+            match self.anchor {
+                OverlayAnchor::Custom(custom) => sub_rect(&screen, &custom),
+                _ => screen,
             }
-            log::warn!("GetClientRect failed for tracked window: {:?}", error);
-            return true;
-        }
+        } else {
+            match self.anchor {
+                OverlayAnchor::Client => client_screen_rect(self.hwnd)?,
+                OverlayAnchor::Window => window_screen_rect(self.hwnd)?,
+                OverlayAnchor::Child(child) => client_screen_rect(child)?,
+                OverlayAnchor::ChildId(id) => {
+                    let child = unsafe { GetDlgItem(Some(self.hwnd), id) }.ok()?;
+                    client_screen_rect(child)?
+                }
+                OverlayAnchor::Custom(custom) => sub_rect(&client_screen_rect(self.hwnd)?, &custom),
+            }
+        };
+        rect.left += self.margins.left;
+        rect.top += self.margins.top;
+        rect.right = (rect.right - self.margins.right).max(rect.left);
+        rect.bottom = (rect.bottom - self.margins.bottom).max(rect.top);
+        Some(rect)
+    }
 
-        unsafe {
-            let _ = ClientToScreen(self.hwnd, &mut rect.left as *mut _ as *mut POINT);
-            let _ = ClientToScreen(self.hwnd, &mut rect.right as *mut _ as *mut POINT);
+    /// 显示器区域，显示器插拔或分辨率变化后重新枚举
+    fn display_bounds(&mut self) -> Option<RECT> {
+        if take_display_changed() || self.display_bounds.is_none() {
+            self.display_bounds = self.display.as_ref().and_then(DisplayTarget::bounds);
         }
+        self.display_bounds
+    }
 
-        if unsafe { GetFocus() } != self.hwnd {
-            rect.bottom -= 1;
-        }
+    /// 跟踪窗口
+    #[allow(dead_code)]
+    pub fn update(&mut self, hwnd: HWND) -> bool {
+        let rect = match self.anchor_bounds() {
+            Some(rect) => rect,
+            None => {
+                let error = unsafe { GetLastError() };
+                if error == ERROR_INVALID_WINDOW_HANDLE {
+                    return false;
+                }
+                log::warn!(
+                    "Failed to resolve anchor bounds of tracked window: {:?}",
+                    error
+                );
+                return true;
+            }
+        };
 
         if rect == self.current_bounds {
             return true;
@@ -130,7 +377,7 @@ impl WindowTracker {
 
         self.current_bounds = rect;
         let width = rect.right - rect.left;
-        let high = rect.bottom - rect.top + 1;
+        let high = rect.bottom - rect.top;
         unsafe {
             WINDOWS_RECT.width = width;
             WINDOWS_RECT.high = high;
@@ -149,29 +396,116 @@ impl WindowTracker {
     /// 跟踪窗口，每调用一次会对目标窗口进行跟踪
     #[allow(dead_code)]
     pub fn tracking(&mut self, hwnd: HWND) -> bool {
-        let mut rect = RECT::default();
-        unsafe {
-            let _ = GetClientRect(self.hwnd, &mut rect);
-            let _ = ClientToScreen(self.hwnd, &mut rect.left as *mut _ as *mut POINT);
-        }
         if !unsafe { IsWindow(Some(self.hwnd)).as_bool() } {
             return false;
         }
+        let rect = match self.anchor_bounds() {
+            Some(rect) => self.predict(rect),
+            None => return true,
+        };
         if self.current_bounds == rect {
             return true;
         }
         self.current_bounds = rect;
+        let width = rect.right - rect.left;
+        let high = rect.bottom - rect.top;
+        unsafe {
+            WINDOWS_RECT.width = width;
+            WINDOWS_RECT.high = high;
+        }
+        // 与监听线程同时移动窗口会来回抖动
+        if self.follow_events {
+            return true;
+        }
         unsafe {
             let _ = SetWindowPos(
                 hwnd,
                 None,
                 rect.left,
                 rect.top,
-                rect.right,
-                rect.bottom,
+                width,
+                high,
                 windows::Win32::UI::WindowsAndMessaging::SWP_SHOWWINDOW,
             );
         }
         true
     }
+
+    /// 目标移动时立即移动覆盖窗口，供事件跟踪线程调用
+    pub(crate) fn follow(&mut self, hwnd: HWND) {
+        let rect = match self.anchor_bounds() {
+            Some(rect) => rect,
+            None => return,
+        };
+        if self.current_bounds == rect {
+            return;
+        }
+        self.current_bounds = rect;
+        unsafe {
+            let _ = SetWindowPos(
+                hwnd,
+                None,
+                rect.left,
+                rect.top,
+                rect.right - rect.left,
+                rect.bottom - rect.top,
+                SWP_NOACTIVATE | SWP_NOZORDER | SWP_ASYNCWINDOWPOS,
+            );
+        }
+    }
+
+    /// 按上一帧的移动速度外推位置，目标停止移动后回到实际位置
+    fn predict(&mut self, rect: RECT) -> RECT {
+        let last = std::mem::replace(&mut self.last_target_bounds, rect);
+        if self.prediction <= 0.0 || last == RECT::default() {
+            return rect;
+        }
+        let dx = ((rect.left - last.left) as f32 * self.prediction).round() as i32;
+        let dy = ((rect.top - last.top) as f32 * self.prediction).round() as i32;
+        RECT {
+            left: rect.left + dx,
+            top: rect.top + dy,
+            right: rect.right + dx,
+            bottom: rect.bottom + dy,
+        }
+    }
+}
+
+/// `base`内的自定义区域
+fn sub_rect(base: &RECT, custom: &AnchorRect) -> RECT {
+    let (x, y, width, height) = custom.resolve(base.right - base.left, base.bottom - base.top);
+    RECT {
+        left: base.left + x,
+        top: base.top + y,
+        right: base.left + x + width,
+        bottom: base.top + y + height,
+    }
+}
+
+/// 窗口客户区的屏幕坐标
+fn client_screen_rect(hwnd: HWND) -> Option<RECT> {
+    let mut rect = RECT::default();
+    unsafe {
+        GetClientRect(hwnd, &mut rect).ok()?;
+        let _ = ClientToScreen(hwnd, &mut rect.left as *mut _ as *mut POINT);
+        let _ = ClientToScreen(hwnd, &mut rect.right as *mut _ as *mut POINT);
+    }
+    Some(rect)
+}
+
+/// 窗口的屏幕坐标，优先使用DWM的可见边界(不含透明的缩放边框)
+fn window_screen_rect(hwnd: HWND) -> Option<RECT> {
+    let mut rect = RECT::default();
+    let dwm = unsafe {
+        DwmGetWindowAttribute(
+            hwnd,
+            DWMWA_EXTENDED_FRAME_BOUNDS,
+            &mut rect as *mut _ as *mut _,
+            size_of::<RECT>() as u32,
+        )
+    };
+    if dwm.is_err() {
+        unsafe { GetWindowRect(hwnd, &mut rect) }.ok()?;
+    }
+    Some(rect)
 }